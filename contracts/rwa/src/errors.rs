@@ -1,3 +1,4 @@
+use linera_sdk::base::Timestamp;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,6 +9,93 @@ pub enum Error {
     #[error("Not owner")]
     NotOwner,
 
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    #[error("Caller is not an approved issuer for this asset type")]
+    NotApprovedIssuer,
+
+    #[error("Caller is not the owner, an approved spender or an operator")]
+    NotApprovedOrOwner,
+
+    #[error("Asset is fractionalized")]
+    Fractionalized,
+
+    #[error("Asset is not fractionalized")]
+    NotFractionalized,
+
+    #[error("Share count must be positive")]
+    InvalidShareCount,
+
+    #[error("Insufficient shares")]
+    InsufficientShares,
+
+    #[error("All shares are required to redeem the asset")]
+    MustHoldAllShares,
+
+    #[error("Recipient is not a verified holder")]
+    RecipientNotVerified,
+
+    #[error("Recipient identity expired at {0}")]
+    IdentityExpired(Timestamp),
+
+    #[error("Holders from country {0} are not allowed for this asset")]
+    CountryNotAllowed(String),
+
+    #[error("Asset is locked up until {0}")]
+    LockedUp(Timestamp),
+
+    #[error("Transfer would exceed the maximum of {0} holders")]
+    MaxHoldersExceeded(u64),
+
+    #[error("Invalid country code: {0}")]
+    InvalidCountryCode(String),
+
+    #[error("Royalty cannot exceed 10000 basis points")]
+    InvalidRoyalty,
+
+    #[error("No custodian registered for this asset type")]
+    NoCustodian,
+
+    #[error("Redemption request not found")]
+    RedemptionNotFound,
+
+    #[error("Redemption is pending for this asset")]
+    RedemptionPending,
+
+    #[error("Redemption request is not pending")]
+    RedemptionNotPending,
+
+    #[error("Caller is not an authorised appraiser")]
+    NotAppraiser,
+
+    #[error("Invalid currency code: {0}")]
+    InvalidCurrency(String),
+
+    #[error("Document not found")]
+    DocumentNotFound,
+
+    #[error("Invalid document: {0}")]
+    InvalidDocument(&'static str),
+
+    #[error("Caller is not an authorised lending application")]
+    NotLendingApplication,
+
+    #[error("Asset is under a lien")]
+    Liened,
+
+    #[error("Asset has no lien")]
+    NoLien,
+
+    #[error("Rental expiry must be in the future")]
+    InvalidExpiry,
+
+    #[error("Asset is rented out")]
+    Rented,
+
+    #[error("Invalid metadata: {0}")]
+    InvalidMetadata(&'static str),
+
     #[error("View error: {0}")]
     ViewError(#[from] linera_views::views::ViewError),
 
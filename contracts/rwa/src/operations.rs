@@ -1,22 +1,190 @@
-use linera_sdk::base::AccountOwner;
+use linera_sdk::base::{AccountOwner, ApplicationId, ChainId, CryptoHash};
 use serde::{Deserialize, Serialize};
 
+use crate::state::{
+    Asset, AssetDocument, AssetMetadata, Identity, Royalty, TokenId, TransferRules, UsageRight,
+    ValuationReport,
+};
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Operation {
     TokenizeAsset {
         to: AccountOwner,
         metadata_uri: String,
         asset_type: String,
+        metadata: AssetMetadata,
+        /// Falls back to the issuer's default royalty when `None`.
+        royalty: Option<Royalty>,
     },
     Transfer {
-        token_id: u64,
+        token_id: TokenId,
+        to: AccountOwner,
+    },
+    /// `None` clears the approval.
+    Approve {
+        token_id: TokenId,
+        spender: Option<AccountOwner>,
+    },
+    SetApprovalForAll {
+        operator: AccountOwner,
+        approved: bool,
+    },
+    /// Locks the asset and issues `shares` fungible shares to the owner.
+    Fractionalize {
+        token_id: TokenId,
+        shares: u128,
+    },
+    TransferShares {
+        token_id: TokenId,
         to: AccountOwner,
+        amount: u128,
+    },
+    /// Burns all shares and hands the asset to the caller, who must hold them.
+    Redeem {
+        token_id: TokenId,
+    },
+    AddIssuer {
+        issuer: AccountOwner,
+        asset_type: String,
+    },
+    RemoveIssuer {
+        issuer: AccountOwner,
+        asset_type: String,
+    },
+    SetIdentity {
+        account: AccountOwner,
+        identity: Identity,
+    },
+    RemoveIdentity {
+        account: AccountOwner,
+    },
+    /// Only the asset's issuer may set its rules.
+    SetTransferRules {
+        token_id: TokenId,
+        rules: TransferRules,
+    },
+    /// Sets the caller's default royalty for assets it tokenizes.
+    SetDefaultRoyalty {
+        royalty: Option<Royalty>,
+    },
+    SetCustodian {
+        asset_type: String,
+        custodian: AccountOwner,
+    },
+    /// Locks the asset until the custodian attests or the holder cancels.
+    RequestRedemption {
+        token_id: TokenId,
+    },
+    CancelRedemption {
+        token_id: TokenId,
+    },
+    /// Confirms the off-chain redemption and burns the token.
+    AttestRedemption {
+        token_id: TokenId,
+        attestation: String,
+    },
+    AddAppraiser {
+        appraiser: AccountOwner,
+    },
+    RemoveAppraiser {
+        appraiser: AccountOwner,
+    },
+    SetValuationStalenessWindow {
+        window_micros: u64,
+    },
+    ReportValuation {
+        token_id: TokenId,
+        value: u128,
+        currency: String,
+    },
+    /// Moves the asset to the RWA application on `destination`, keeping its
+    /// token id.
+    TransferToChain {
+        token_id: TokenId,
+        destination: ChainId,
+        recipient: AccountOwner,
+    },
+    /// Appends a new version of document `name`; owner or issuer only.
+    AnchorDocument {
+        token_id: TokenId,
+        name: String,
+        content_hash: CryptoHash,
+        ipfs_cid: Option<String>,
+    },
+    SetLendingApplication {
+        application_id: ApplicationId,
+        authorized: bool,
+    },
+    /// Grants `right.user` the right to use the asset until `right.expires`;
+    /// `None` ends the current rental.
+    SetUser {
+        token_id: TokenId,
+        right: Option<UsageRight>,
     },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Query {
-    GetAsset { token_id: u64 },
-    GetOwnerTokens { owner: AccountOwner },
-    GetTokenURI { token_id: u64 },
+    GetAsset { token_id: TokenId },
+    GetOwnerTokens { owner: AccountOwner, offset: u32, limit: u32 },
+    GetOwnerTokenCount { owner: AccountOwner },
+    GetAllTokens { offset: u32, limit: u32 },
+    GetTokenURI { token_id: TokenId },
+    GetAssetMetadata { token_id: TokenId },
+    GetApproved { token_id: TokenId },
+    IsApprovedForAll { owner: AccountOwner, operator: AccountOwner },
+    GetFractions { token_id: TokenId },
+    GetShareBalance { token_id: TokenId, owner: AccountOwner },
+    IsApprovedIssuer { issuer: AccountOwner, asset_type: String },
+    GetIdentity { account: AccountOwner },
+    GetTransferRules { token_id: TokenId },
+    RoyaltyInfo { token_id: TokenId, sale_price: u128 },
+    GetRedemption { token_id: TokenId },
+    GetLatestValuation { token_id: TokenId },
+    /// Newest reports first.
+    GetValuationHistory { token_id: TokenId, offset: u32, limit: u32 },
+    /// Current user, or `None` once the rental has expired.
+    GetUser { token_id: TokenId },
+    /// Latest version of each document.
+    GetDocuments { token_id: TokenId },
+    /// Checks `content_hash` against the given version, or the latest one.
+    VerifyDocument {
+        token_id: TokenId,
+        name: String,
+        content_hash: CryptoHash,
+        version: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum Message {
+    /// Re-creates an asset sent from another chain. If the message bounces,
+    /// the sending chain restores it to `asset.owner`.
+    ReceiveAsset {
+        token_id: TokenId,
+        asset: Asset,
+        recipient: AccountOwner,
+        transfer_rules: Option<TransferRules>,
+        valuations: Vec<ValuationReport>,
+        documents: Vec<AssetDocument>,
+    },
+}
+
+/// Calls from lending applications registered with
+/// `Operation::SetLendingApplication`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ApplicationCall {
+    /// Requires the asset owner to have signed the block.
+    PlaceLien {
+        token_id: TokenId,
+        loan_id: u64,
+    },
+    ReleaseLien {
+        token_id: TokenId,
+    },
+    /// Hands a liened asset to `recipient` when the loan defaults.
+    Seize {
+        token_id: TokenId,
+        recipient: AccountOwner,
+    },
 }
\ No newline at end of file
@@ -1,80 +1,764 @@
 use linera_sdk::{
-    base::{AccountOwner, ContractRuntime, ServiceRuntime},
-    views::{MapView, RegisterView, ViewStorageContext},
+    base::{
+        AccountOwner, ApplicationId, ChainId, ContractRuntime, CryptoHash, ServiceRuntime,
+        Timestamp,
+    },
+    views::{CollectionView, MapView, RegisterView, SetView, ViewStorageContext},
 };
 use linera_views::views::ViewError;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use super::errors::Error;
-use super::operations::{Operation, Query};
+use super::operations::{ApplicationCall, Message, Operation, Query};
+
+const MAX_LEGAL_IDENTIFIER_LEN: usize = 128;
+const MAX_ATTRIBUTES: usize = 32;
+const MAX_ATTRIBUTE_KEY_LEN: usize = 64;
+const MAX_ATTRIBUTE_VALUE_LEN: usize = 256;
+const BPS_DENOMINATOR: u128 = 10_000;
+const MAX_PAGE_SIZE: usize = 50;
+const MAX_DOCUMENT_NAME_LEN: usize = 128;
+const MAX_IPFS_CID_LEN: usize = 128;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetClass {
+    RealEstate,
+    Invoice,
+    Commodity,
+    Equipment,
+    Vehicle,
+    Art,
+    Bond,
+    Equity,
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AssetMetadata {
+    pub asset_class: AssetClass,
+    /// ISO 3166-1 alpha-2 country code, e.g. "DE".
+    pub jurisdiction: String,
+    /// Valuation in minor units of `currency`.
+    pub valuation: u128,
+    /// ISO 4217 currency code, e.g. "EUR".
+    pub currency: String,
+    /// Land registry number, invoice number, ISIN, ...
+    pub legal_identifier: String,
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl AssetMetadata {
+    pub fn validate(&self) -> Result<(), Error> {
+        if !is_uppercase_code(&self.jurisdiction, 2) {
+            return Err(Error::InvalidMetadata("jurisdiction must be an ISO 3166-1 alpha-2 code"));
+        }
+        if !is_uppercase_code(&self.currency, 3) {
+            return Err(Error::InvalidMetadata("currency must be an ISO 4217 code"));
+        }
+        if self.valuation == 0 {
+            return Err(Error::InvalidMetadata("valuation must be positive"));
+        }
+        if self.legal_identifier.is_empty() || self.legal_identifier.len() > MAX_LEGAL_IDENTIFIER_LEN {
+            return Err(Error::InvalidMetadata("legal identifier is empty or too long"));
+        }
+        if self.attributes.len() > MAX_ATTRIBUTES {
+            return Err(Error::InvalidMetadata("too many attributes"));
+        }
+        for (key, value) in &self.attributes {
+            if key.is_empty() || key.len() > MAX_ATTRIBUTE_KEY_LEN {
+                return Err(Error::InvalidMetadata("attribute key is empty or too long"));
+            }
+            if value.len() > MAX_ATTRIBUTE_VALUE_LEN {
+                return Err(Error::InvalidMetadata("attribute value is too long"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// EIP-2981 style royalty: `basis_points` of each sale price goes to `receiver`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Royalty {
+    pub receiver: AccountOwner,
+    pub basis_points: u16,
+}
+
+impl Royalty {
+    pub fn validate(&self) -> Result<(), Error> {
+        if u128::from(self.basis_points) > BPS_DENOMINATOR {
+            return Err(Error::InvalidRoyalty);
+        }
+        Ok(())
+    }
+
+    pub fn amount(&self, sale_price: u128) -> u128 {
+        let bps = u128::from(self.basis_points);
+        sale_price / BPS_DENOMINATOR * bps + sale_price % BPS_DENOMINATOR * bps / BPS_DENOMINATOR
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RoyaltyPayment {
+    pub receiver: AccountOwner,
+    pub amount: u128,
+}
+
+/// A chain the asset left, and who owned it there.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProvenanceRecord {
+    pub chain_id: ChainId,
+    pub owner: AccountOwner,
+    pub departed_at: Timestamp,
+}
+
+/// Right to use an asset until `expires`, separate from ownership.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UsageRight {
+    pub user: AccountOwner,
+    pub expires: Timestamp,
+}
+
+/// Collateral lock held by a lending application.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Lien {
+    pub lender: ApplicationId,
+    /// Lender's own reference for the loan.
+    pub loan_id: u64,
+    pub placed_at: Timestamp,
+}
+
+/// Identifies a token on every chain: the chain that tokenized it and its
+/// index there. It stays the same as the asset moves between chains.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenId {
+    pub chain_id: ChainId,
+    pub index: u64,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Asset {
     pub owner: AccountOwner,
     pub metadata_uri: String,
     pub asset_type: String,
+    pub metadata: AssetMetadata,
+    pub issuer: AccountOwner,
+    pub royalty: Option<Royalty>,
+    /// Cross-chain hops, oldest first.
+    pub provenance: Vec<ProvenanceRecord>,
+    pub lien: Option<Lien>,
+}
+
+/// Eligibility record maintained by the admin for each holder.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Identity {
+    pub verified: bool,
+    /// ISO 3166-1 alpha-2 country code.
+    pub country_code: String,
+    pub expires_at: Timestamp,
+}
+
+/// Restrictions an issuer places on who may hold an asset.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransferRules {
+    /// Empty means any country.
+    pub allowed_countries: Vec<String>,
+    /// Upper bound on share holders once the asset is fractionalized.
+    pub max_holders: Option<u64>,
+    pub lockup_until: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedemptionStatus {
+    /// Requested by the holder, waiting for the custodian.
+    Pending,
+    /// Attested by the custodian; the token has been burned.
+    Completed,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RedemptionRequest {
+    pub requester: AccountOwner,
+    pub custodian: AccountOwner,
+    pub requested_at: Timestamp,
+    pub status: RedemptionStatus,
+    /// Custodian's reference for the off-chain hand-over.
+    pub attestation: Option<String>,
+    pub completed_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValuationReport {
+    pub appraiser: AccountOwner,
+    /// Value in minor units of `currency`.
+    pub value: u128,
+    /// ISO 4217 currency code.
+    pub currency: String,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LatestValuation {
+    pub report: ValuationReport,
+    /// No report arrived within the configured staleness window.
+    pub stale: bool,
+}
+
+/// One version of a legal document attached to an asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AssetDocument {
+    pub name: String,
+    /// Starts at 1 and increases with each version of the same name.
+    pub version: u32,
+    pub content_hash: CryptoHash,
+    pub ipfs_cid: Option<String>,
+    pub anchored_by: AccountOwner,
+    pub anchored_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DocumentVerification {
+    pub matches: bool,
+    /// The anchored version the hash was compared against.
+    pub document: AssetDocument,
+}
+
+/// Shares issued against a locked asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Fractions {
+    pub fractionalized_by: AccountOwner,
+    pub total_shares: u128,
+    pub holder_count: u64,
 }
 
 #[derive(linera_sdk::views::ViewStorage)]
 pub struct RwaState {
+    pub admin: RegisterView<AccountOwner>,
+    /// (issuer, asset_type) pairs allowed to tokenize assets of that type.
+    pub issuers: SetView<(AccountOwner, String)>,
     pub next_token_id: RegisterView<u64>,
-    pub assets: MapView<u64, Asset>,
-    pub owner_tokens: MapView<AccountOwner, Vec<u64>>,  // Use SetView for production
+    pub assets: MapView<TokenId, Asset>,
+    pub owner_tokens: CollectionView<AccountOwner, SetView<TokenId>>,
+    pub owner_token_count: MapView<AccountOwner, u64>,
+    pub token_approvals: MapView<TokenId, AccountOwner>,
+    /// (owner, operator) pairs allowed to move all of the owner's tokens.
+    pub operators: SetView<(AccountOwner, AccountOwner)>,
+    pub fractions: MapView<TokenId, Fractions>,
+    pub share_balances: MapView<(TokenId, AccountOwner), u128>,
+    pub identities: MapView<AccountOwner, Identity>,
+    pub transfer_rules: MapView<TokenId, TransferRules>,
+    /// Royalty applied when an issuer tokenizes without specifying one.
+    pub default_royalties: MapView<AccountOwner, Royalty>,
+    /// Custodian holding the underlying assets, per asset type.
+    pub custodians: MapView<String, AccountOwner>,
+    pub redemptions: MapView<TokenId, RedemptionRequest>,
+    pub appraisers: SetView<AccountOwner>,
+    /// Valuation reports keyed by (token_id, sequence number), oldest first.
+    pub valuations: MapView<(TokenId, u64), ValuationReport>,
+    /// Number of valuation reports per token.
+    pub valuation_count: MapView<TokenId, u64>,
+    /// Age after which the latest valuation is stale; 0 disables the check.
+    pub valuation_staleness_micros: RegisterView<u64>,
+    /// Every anchored document version, keyed by (token_id, name, version).
+    pub documents: MapView<(TokenId, String, u32), AssetDocument>,
+    /// Latest version of each document name, per token.
+    pub document_versions: CollectionView<TokenId, MapView<String, u32>>,
+    /// Applications allowed to place liens through `ApplicationCall`.
+    pub lending_applications: SetView<ApplicationId>,
+    /// Rentals; an entry past its expiry is treated as absent. Cleared
+    /// whenever the asset changes owner.
+    pub usage_rights: MapView<TokenId, UsageRight>,
 }
 
 #[contract]
 impl Contract for RwaState {
     type Error = Error;
     type Operation = Operation;
-    type ApplicationCall = ();
+    type Message = Message;
+    type ApplicationCall = ApplicationCall;
+    type Response = ();
     type SessionState = ();
+    type InstantiationArgument = ();
 
     async fn new(runtime: ContractRuntime<Self>) -> Result<Self, Self::Error> {
         let context = ViewStorageContext::from(runtime.root_view_storage_context());
         Ok(Self {
+            admin: RegisterView::load(context.clone().sub("admin"))?,
+            issuers: SetView::load(context.clone().sub("issuers"))?,
             next_token_id: RegisterView::load(context.clone().sub("next_id"))?,
             assets: MapView::load(context.clone().sub("assets"))?,
-            owner_tokens: MapView::load(context.sub("owner_tokens"))?,
+            owner_tokens: CollectionView::load(context.clone().sub("owner_tokens"))?,
+            owner_token_count: MapView::load(context.clone().sub("owner_token_count"))?,
+            token_approvals: MapView::load(context.clone().sub("token_approvals"))?,
+            operators: SetView::load(context.clone().sub("operators"))?,
+            fractions: MapView::load(context.clone().sub("fractions"))?,
+            share_balances: MapView::load(context.clone().sub("share_balances"))?,
+            identities: MapView::load(context.clone().sub("identities"))?,
+            transfer_rules: MapView::load(context.clone().sub("transfer_rules"))?,
+            default_royalties: MapView::load(context.clone().sub("default_royalties"))?,
+            custodians: MapView::load(context.clone().sub("custodians"))?,
+            redemptions: MapView::load(context.clone().sub("redemptions"))?,
+            appraisers: SetView::load(context.clone().sub("appraisers"))?,
+            valuations: MapView::load(context.clone().sub("valuations"))?,
+            valuation_count: MapView::load(context.clone().sub("valuation_count"))?,
+            valuation_staleness_micros: RegisterView::load(context.clone().sub("valuation_staleness"))?,
+            documents: MapView::load(context.clone().sub("documents"))?,
+            document_versions: CollectionView::load(context.clone().sub("document_versions"))?,
+            lending_applications: SetView::load(context.clone().sub("lending_applications"))?,
+            usage_rights: MapView::load(context.sub("usage_rights"))?,
         })
     }
 
+    async fn instantiate(&mut self, _argument: ()) -> Result<(), Self::Error> {
+        let creator = self.runtime().authenticated_signer();
+        self.admin.set(creator);
+        Ok(())
+    }
+
     async fn execute_operation(&mut self, operation: Operation) -> Result<(), Self::Error> {
         let caller = self.runtime().authenticated_signer();
 
         match operation {
-            Operation::TokenizeAsset { to, metadata_uri, asset_type } => {
-                let mut id = self.next_token_id.get().await?;
-                id += 1;
-                self.next_token_id.set(id);
+            Operation::TokenizeAsset {
+                to,
+                metadata_uri,
+                asset_type,
+                metadata,
+                royalty,
+            } => {
+                if !self.issuers.contains(&(caller, asset_type.clone())).await? {
+                    return Err(Error::NotApprovedIssuer);
+                }
+                metadata.validate()?;
+                let royalty = match royalty {
+                    Some(royalty) => Some(royalty),
+                    None => self.default_royalties.get(&caller).await?,
+                };
+                if let Some(royalty) = &royalty {
+                    royalty.validate()?;
+                }
+                let index = self.next_token_id.get().await? + 1;
+                self.next_token_id.set(index);
+                let id = TokenId {
+                    chain_id: self.runtime().chain_id(),
+                    index,
+                };
 
                 let asset = Asset {
                     owner: to,
                     metadata_uri,
                     asset_type,
+                    metadata,
+                    issuer: caller,
+                    royalty,
+                    provenance: Vec::new(),
+                    lien: None,
                 };
                 self.assets.insert(&id, asset)?;
-
-                let mut tokens = self.owner_tokens.get(&to).await?.unwrap_or_default();
-                tokens.push(id);
-                self.owner_tokens.insert(&to, tokens)?;
+                self.add_owner_token(&to, id).await?;
             }
             Operation::Transfer { token_id, to } => {
                 let mut asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
                 let from = asset.owner;
+                if !self.is_approved_or_owner(&caller, token_id, &from).await? {
+                    return Err(Error::NotApprovedOrOwner);
+                }
+                self.ensure_transferable(token_id).await?;
+                self.check_compliance(token_id, &to, None).await?;
+                asset.owner = to;
+                self.assets.insert(&token_id, asset)?;
+                self.token_approvals.remove(&token_id)?;
+                self.usage_rights.remove(&token_id)?;
+                self.move_token(token_id, &from, &to).await?;
+            }
+            Operation::Approve { token_id, spender } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                if asset.owner != caller && !self.operators.contains(&(asset.owner, caller)).await? {
+                    return Err(Error::NotOwner);
+                }
+                if self.fractions.contains_key(&token_id).await? {
+                    return Err(Error::Fractionalized);
+                }
+                match spender {
+                    Some(spender) => self.token_approvals.insert(&token_id, spender)?,
+                    None => self.token_approvals.remove(&token_id)?,
+                }
+            }
+            Operation::SetApprovalForAll { operator, approved } => {
+                if approved {
+                    self.operators.insert(&(caller, operator))?;
+                } else {
+                    self.operators.remove(&(caller, operator))?;
+                }
+            }
+            Operation::Fractionalize { token_id, shares } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                if asset.owner != caller {
+                    return Err(Error::NotOwner);
+                }
+                self.ensure_transferable(token_id).await?;
+                self.ensure_not_rented(token_id).await?;
+                if shares == 0 {
+                    return Err(Error::InvalidShareCount);
+                }
+                let fractions = Fractions {
+                    fractionalized_by: caller,
+                    total_shares: shares,
+                    holder_count: 1,
+                };
+                self.fractions.insert(&token_id, fractions)?;
+                self.share_balances.insert(&(token_id, caller), shares)?;
+                self.token_approvals.remove(&token_id)?;
+            }
+            Operation::TransferShares { token_id, to, amount } => {
+                let mut fractions = self.fractions.get(&token_id).await?.ok_or(Error::NotFractionalized)?;
+                let from_balance = self.share_balances.get(&(token_id, caller)).await?.unwrap_or(0);
+                if from_balance < amount {
+                    return Err(Error::InsufficientShares);
+                }
+                if amount == 0 || caller == to {
+                    return Ok(());
+                }
+                let to_balance = self.share_balances.get(&(token_id, to)).await?.unwrap_or(0);
+                if to_balance == 0 {
+                    fractions.holder_count += 1;
+                }
+                if from_balance == amount {
+                    fractions.holder_count -= 1;
+                }
+                self.check_compliance(token_id, &to, Some(fractions.holder_count)).await?;
+                if from_balance == amount {
+                    self.share_balances.remove(&(token_id, caller))?;
+                } else {
+                    self.share_balances.insert(&(token_id, caller), from_balance - amount)?;
+                }
+                self.share_balances.insert(&(token_id, to), to_balance + amount)?;
+                self.fractions.insert(&token_id, fractions)?;
+            }
+            Operation::Redeem { token_id } => {
+                let fractions = self.fractions.get(&token_id).await?.ok_or(Error::NotFractionalized)?;
+                let balance = self.share_balances.get(&(token_id, caller)).await?.unwrap_or(0);
+                if balance != fractions.total_shares {
+                    return Err(Error::MustHoldAllShares);
+                }
+                self.share_balances.remove(&(token_id, caller))?;
+                self.fractions.remove(&token_id)?;
+
+                let mut asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                let from = asset.owner;
+                asset.owner = caller;
+                self.assets.insert(&token_id, asset)?;
                 if from != caller {
+                    self.token_approvals.remove(&token_id)?;
+                    self.usage_rights.remove(&token_id)?;
+                    self.move_token(token_id, &from, &caller).await?;
+                }
+            }
+            Operation::AddIssuer { issuer, asset_type } => {
+                self.ensure_admin(&caller).await?;
+                self.issuers.insert(&(issuer, asset_type))?;
+            }
+            Operation::RemoveIssuer { issuer, asset_type } => {
+                self.ensure_admin(&caller).await?;
+                self.issuers.remove(&(issuer, asset_type))?;
+            }
+            Operation::SetIdentity { account, identity } => {
+                self.ensure_admin(&caller).await?;
+                if !is_uppercase_code(&identity.country_code, 2) {
+                    return Err(Error::InvalidCountryCode(identity.country_code));
+                }
+                self.identities.insert(&account, identity)?;
+            }
+            Operation::RemoveIdentity { account } => {
+                self.ensure_admin(&caller).await?;
+                self.identities.remove(&account)?;
+            }
+            Operation::SetTransferRules { token_id, rules } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                if asset.issuer != caller {
+                    return Err(Error::Unauthorized);
+                }
+                if let Some(code) = rules.allowed_countries.iter().find(|code| !is_uppercase_code(code, 2)) {
+                    return Err(Error::InvalidCountryCode(code.clone()));
+                }
+                self.transfer_rules.insert(&token_id, rules)?;
+            }
+            Operation::SetDefaultRoyalty { royalty } => {
+                match royalty {
+                    Some(royalty) => {
+                        royalty.validate()?;
+                        self.default_royalties.insert(&caller, royalty)?;
+                    }
+                    None => self.default_royalties.remove(&caller)?,
+                }
+            }
+            Operation::SetCustodian { asset_type, custodian } => {
+                self.ensure_admin(&caller).await?;
+                self.custodians.insert(&asset_type, custodian)?;
+            }
+            Operation::RequestRedemption { token_id } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                if asset.owner != caller {
                     return Err(Error::NotOwner);
                 }
-                asset.owner = to;
+                self.ensure_transferable(token_id).await?;
+                self.ensure_not_rented(token_id).await?;
+                let custodian = self.custodians.get(&asset.asset_type).await?.ok_or(Error::NoCustodian)?;
+                let request = RedemptionRequest {
+                    requester: caller,
+                    custodian,
+                    requested_at: self.runtime().system_time(),
+                    status: RedemptionStatus::Pending,
+                    attestation: None,
+                    completed_at: None,
+                };
+                self.redemptions.insert(&token_id, request)?;
+            }
+            Operation::CancelRedemption { token_id } => {
+                let mut request = self.pending_redemption(token_id).await?;
+                if request.requester != caller {
+                    return Err(Error::Unauthorized);
+                }
+                request.status = RedemptionStatus::Cancelled;
+                self.redemptions.insert(&token_id, request)?;
+            }
+            Operation::AttestRedemption { token_id, attestation } => {
+                let mut request = self.pending_redemption(token_id).await?;
+                if request.custodian != caller {
+                    return Err(Error::Unauthorized);
+                }
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+
+                // Burn the token
+                self.assets.remove(&token_id)?;
+                self.remove_owner_token(&asset.owner, token_id).await?;
+                self.token_approvals.remove(&token_id)?;
+                self.usage_rights.remove(&token_id)?;
+                self.transfer_rules.remove(&token_id)?;
+                self.remove_valuations(token_id).await?;
+                self.remove_documents(token_id).await?;
+
+                request.status = RedemptionStatus::Completed;
+                request.attestation = Some(attestation);
+                request.completed_at = Some(self.runtime().system_time());
+                self.redemptions.insert(&token_id, request)?;
+            }
+            Operation::AddAppraiser { appraiser } => {
+                self.ensure_admin(&caller).await?;
+                self.appraisers.insert(&appraiser)?;
+            }
+            Operation::RemoveAppraiser { appraiser } => {
+                self.ensure_admin(&caller).await?;
+                self.appraisers.remove(&appraiser)?;
+            }
+            Operation::SetValuationStalenessWindow { window_micros } => {
+                self.ensure_admin(&caller).await?;
+                self.valuation_staleness_micros.set(window_micros);
+            }
+            Operation::ReportValuation { token_id, value, currency } => {
+                if !self.appraisers.contains(&caller).await? {
+                    return Err(Error::NotAppraiser);
+                }
+                if !self.assets.contains_key(&token_id).await? {
+                    return Err(Error::TokenNotFound);
+                }
+                if !is_uppercase_code(&currency, 3) {
+                    return Err(Error::InvalidCurrency(currency));
+                }
+                let report = ValuationReport {
+                    appraiser: caller,
+                    value,
+                    currency,
+                    timestamp: self.runtime().system_time(),
+                };
+                let count = self.valuation_count.get(&token_id).await?.unwrap_or(0);
+                self.valuations.insert(&(token_id, count), report)?;
+                self.valuation_count.insert(&token_id, count + 1)?;
+            }
+            Operation::TransferToChain {
+                token_id,
+                destination,
+                recipient,
+            } => {
+                let mut asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                let from = asset.owner;
+                if !self.is_approved_or_owner(&caller, token_id, &from).await? {
+                    return Err(Error::NotApprovedOrOwner);
+                }
+                self.ensure_transferable(token_id).await?;
+                self.check_compliance(token_id, &recipient, None).await?;
+                asset.provenance.push(ProvenanceRecord {
+                    chain_id: self.runtime().chain_id(),
+                    owner: from,
+                    departed_at: self.runtime().system_time(),
+                });
+                let message = Message::ReceiveAsset {
+                    token_id,
+                    asset,
+                    recipient,
+                    transfer_rules: self.transfer_rules.get(&token_id).await?,
+                    valuations: self.remove_valuations(token_id).await?,
+                    documents: self.remove_documents(token_id).await?,
+                };
+                self.runtime()
+                    .prepare_message(message)
+                    .with_tracking()
+                    .send_to(destination);
+
+                self.assets.remove(&token_id)?;
+                self.remove_owner_token(&from, token_id).await?;
+                self.token_approvals.remove(&token_id)?;
+                self.usage_rights.remove(&token_id)?;
+                self.transfer_rules.remove(&token_id)?;
+            }
+            Operation::AnchorDocument {
+                token_id,
+                name,
+                content_hash,
+                ipfs_cid,
+            } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                if asset.owner != caller && asset.issuer != caller {
+                    return Err(Error::Unauthorized);
+                }
+                if name.is_empty() || name.len() > MAX_DOCUMENT_NAME_LEN {
+                    return Err(Error::InvalidDocument("name is empty or too long"));
+                }
+                if ipfs_cid.as_ref().is_some_and(|cid| cid.is_empty() || cid.len() > MAX_IPFS_CID_LEN) {
+                    return Err(Error::InvalidDocument("IPFS CID is empty or too long"));
+                }
+                let version = {
+                    let mut versions = self.document_versions.load_entry_mut(&token_id).await?;
+                    let version = versions.get(&name).await?.unwrap_or(0) + 1;
+                    versions.insert(&name, version)?;
+                    version
+                };
+                let document = AssetDocument {
+                    name: name.clone(),
+                    version,
+                    content_hash,
+                    ipfs_cid,
+                    anchored_by: caller,
+                    anchored_at: self.runtime().system_time(),
+                };
+                self.documents.insert(&(token_id, name, version), document)?;
+            }
+            Operation::SetLendingApplication {
+                application_id,
+                authorized,
+            } => {
+                self.ensure_admin(&caller).await?;
+                if authorized {
+                    self.lending_applications.insert(&application_id)?;
+                } else {
+                    self.lending_applications.remove(&application_id)?;
+                }
+            }
+            Operation::SetUser { token_id, right } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                if !self.is_approved_or_owner(&caller, token_id, &asset.owner).await? {
+                    return Err(Error::NotApprovedOrOwner);
+                }
+                match right {
+                    Some(right) => {
+                        // Shareholders and the custodian expect the asset as is
+                        if self.fractions.contains_key(&token_id).await? {
+                            return Err(Error::Fractionalized);
+                        }
+                        let redemption = self.redemptions.get(&token_id).await?;
+                        if redemption.is_some_and(|request| request.status == RedemptionStatus::Pending) {
+                            return Err(Error::RedemptionPending);
+                        }
+                        if right.expires <= self.runtime().system_time() {
+                            return Err(Error::InvalidExpiry);
+                        }
+                        self.usage_rights.insert(&token_id, right)?;
+                    }
+                    None => self.usage_rights.remove(&token_id)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_application_call(&mut self, call: ApplicationCall) -> Result<(), Self::Error> {
+        let lender = self.runtime().authenticated_caller_id().ok_or(Error::Unauthorized)?;
+        match call {
+            ApplicationCall::PlaceLien { token_id, loan_id } => {
+                if !self.lending_applications.contains(&lender).await? {
+                    return Err(Error::NotLendingApplication);
+                }
+                let mut asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                // The owner consents by signing the block that triggers the call
+                if asset.owner != self.runtime().authenticated_signer() {
+                    return Err(Error::NotOwner);
+                }
+                self.ensure_transferable(token_id).await?;
+                asset.lien = Some(Lien {
+                    lender,
+                    loan_id,
+                    placed_at: self.runtime().system_time(),
+                });
+                self.assets.insert(&token_id, asset)?;
+                self.token_approvals.remove(&token_id)?;
+            }
+            ApplicationCall::ReleaseLien { token_id } => {
+                let mut asset = self.liened_asset(token_id, lender).await?;
+                asset.lien = None;
                 self.assets.insert(&token_id, asset)?;
+            }
+            ApplicationCall::Seize { token_id, recipient } => {
+                let mut asset = self.liened_asset(token_id, lender).await?;
+                self.check_compliance(token_id, &recipient, None).await?;
+                let from = asset.owner;
+                asset.owner = recipient;
+                asset.lien = None;
+                self.assets.insert(&token_id, asset)?;
+                self.token_approvals.remove(&token_id)?;
+                self.usage_rights.remove(&token_id)?;
+                self.move_token(token_id, &from, &recipient).await?;
+            }
+        }
+        Ok(())
+    }
 
-                // Update owner_tokens (remove from from, add to to)
-                if let Some(mut from_tokens) = self.owner_tokens.get(&from).await? {
-                    from_tokens.retain(|&x| x != token_id);
-                    self.owner_tokens.insert(&from, from_tokens)?;
+    async fn execute_message(&mut self, message: Message) -> Result<(), Self::Error> {
+        match message {
+            Message::ReceiveAsset {
+                token_id,
+                mut asset,
+                recipient,
+                transfer_rules,
+                valuations,
+                documents,
+            } => {
+                // A bounced message returns the asset to its previous owner
+                // on the sending chain, undoing the provenance hop
+                if self.runtime().message_is_bouncing() {
+                    asset.provenance.pop();
+                } else {
+                    asset.owner = recipient;
+                }
+                let owner = asset.owner;
+                self.assets.insert(&token_id, asset)?;
+                self.add_owner_token(&owner, token_id).await?;
+                if let Some(rules) = transfer_rules {
+                    self.transfer_rules.insert(&token_id, rules)?;
+                }
+                if !valuations.is_empty() {
+                    self.valuation_count.insert(&token_id, valuations.len() as u64)?;
+                }
+                for (sequence, report) in (0..).zip(valuations) {
+                    self.valuations.insert(&(token_id, sequence), report)?;
+                }
+                for document in documents {
+                    let mut versions = self.document_versions.load_entry_mut(&token_id).await?;
+                    let latest = versions.get(&document.name).await?.unwrap_or(0);
+                    versions.insert(&document.name, latest.max(document.version))?;
+                    let key = (token_id, document.name.clone(), document.version);
+                    self.documents.insert(&key, document)?;
                 }
-                let mut to_tokens = self.owner_tokens.get(&to).await?.unwrap_or_default();
-                to_tokens.push(token_id);
-                self.owner_tokens.insert(&to, to_tokens)?;
             }
         }
         Ok(())
@@ -89,9 +773,29 @@ impl Service for RwaState {
     async fn new(runtime: ServiceRuntime<Self>) -> Result<Self, Self::Error> {
         let context = ViewStorageContext::from(runtime.root_view_storage_context());
         Ok(Self {
+            admin: RegisterView::load(context.clone().sub("admin"))?,
+            issuers: SetView::load(context.clone().sub("issuers"))?,
             next_token_id: RegisterView::load(context.clone().sub("next_id"))?,
             assets: MapView::load(context.clone().sub("assets"))?,
-            owner_tokens: MapView::load(context.sub("owner_tokens"))?,
+            owner_tokens: CollectionView::load(context.clone().sub("owner_tokens"))?,
+            owner_token_count: MapView::load(context.clone().sub("owner_token_count"))?,
+            token_approvals: MapView::load(context.clone().sub("token_approvals"))?,
+            operators: SetView::load(context.clone().sub("operators"))?,
+            fractions: MapView::load(context.clone().sub("fractions"))?,
+            share_balances: MapView::load(context.clone().sub("share_balances"))?,
+            identities: MapView::load(context.clone().sub("identities"))?,
+            transfer_rules: MapView::load(context.clone().sub("transfer_rules"))?,
+            default_royalties: MapView::load(context.clone().sub("default_royalties"))?,
+            custodians: MapView::load(context.clone().sub("custodians"))?,
+            redemptions: MapView::load(context.clone().sub("redemptions"))?,
+            appraisers: SetView::load(context.clone().sub("appraisers"))?,
+            valuations: MapView::load(context.clone().sub("valuations"))?,
+            valuation_count: MapView::load(context.clone().sub("valuation_count"))?,
+            valuation_staleness_micros: RegisterView::load(context.clone().sub("valuation_staleness"))?,
+            documents: MapView::load(context.clone().sub("documents"))?,
+            document_versions: CollectionView::load(context.clone().sub("document_versions"))?,
+            lending_applications: SetView::load(context.clone().sub("lending_applications"))?,
+            usage_rights: MapView::load(context.sub("usage_rights"))?,
         })
     }
 
@@ -101,14 +805,445 @@ impl Service for RwaState {
                 let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
                 Ok(serde_json::to_string(&asset)?)
             }
-            Query::GetOwnerTokens { owner } => {
-                let tokens = self.owner_tokens.get(&owner).await?.unwrap_or_default();
+            Query::GetOwnerTokens { owner, offset, limit } => {
+                let (offset, limit) = (offset as usize, (limit as usize).min(MAX_PAGE_SIZE));
+                let mut tokens = Vec::new();
+                if let Some(set) = self.owner_tokens.try_load_entry(&owner).await? {
+                    let mut skipped = 0;
+                    set.for_each_index_while(|token_id| {
+                        if skipped < offset {
+                            skipped += 1;
+                        } else {
+                            tokens.push(token_id);
+                        }
+                        Ok(tokens.len() < limit)
+                    })
+                    .await?;
+                }
+                Ok(serde_json::to_string(&tokens)?)
+            }
+            Query::GetOwnerTokenCount { owner } => {
+                let count = self.owner_token_count.get(&owner).await?.unwrap_or(0);
+                Ok(serde_json::to_string(&count)?)
+            }
+            Query::GetAllTokens { offset, limit } => {
+                let (offset, limit) = (offset as usize, (limit as usize).min(MAX_PAGE_SIZE));
+                let mut tokens = Vec::new();
+                let mut skipped = 0;
+                self.assets
+                    .for_each_index_while(|token_id| {
+                        if skipped < offset {
+                            skipped += 1;
+                        } else {
+                            tokens.push(token_id);
+                        }
+                        Ok(tokens.len() < limit)
+                    })
+                    .await?;
                 Ok(serde_json::to_string(&tokens)?)
             }
             Query::GetTokenURI { token_id } => {
                 let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
                 Ok(serde_json::to_string(&asset.metadata_uri)?)
             }
+            Query::GetAssetMetadata { token_id } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                Ok(serde_json::to_string(&asset.metadata)?)
+            }
+            Query::GetApproved { token_id } => {
+                if !self.assets.contains_key(&token_id).await? {
+                    return Err(Error::TokenNotFound);
+                }
+                let approved = self.token_approvals.get(&token_id).await?;
+                Ok(serde_json::to_string(&approved)?)
+            }
+            Query::IsApprovedForAll { owner, operator } => {
+                let approved = self.operators.contains(&(owner, operator)).await?;
+                Ok(serde_json::to_string(&approved)?)
+            }
+            Query::GetFractions { token_id } => {
+                let fractions = self.fractions.get(&token_id).await?.ok_or(Error::NotFractionalized)?;
+                Ok(serde_json::to_string(&fractions)?)
+            }
+            Query::GetShareBalance { token_id, owner } => {
+                let balance = self.share_balances.get(&(token_id, owner)).await?.unwrap_or(0);
+                Ok(serde_json::to_string(&balance)?)
+            }
+            Query::IsApprovedIssuer { issuer, asset_type } => {
+                let approved = self.issuers.contains(&(issuer, asset_type)).await?;
+                Ok(serde_json::to_string(&approved)?)
+            }
+            Query::GetIdentity { account } => {
+                let identity = self.identities.get(&account).await?;
+                Ok(serde_json::to_string(&identity)?)
+            }
+            Query::GetTransferRules { token_id } => {
+                let rules = self.transfer_rules.get(&token_id).await?.unwrap_or_default();
+                Ok(serde_json::to_string(&rules)?)
+            }
+            Query::RoyaltyInfo { token_id, sale_price } => {
+                let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+                let payment = asset.royalty.map(|royalty| RoyaltyPayment {
+                    receiver: royalty.receiver,
+                    amount: royalty.amount(sale_price),
+                });
+                Ok(serde_json::to_string(&payment)?)
+            }
+            Query::GetRedemption { token_id } => {
+                let request = self.redemptions.get(&token_id).await?.ok_or(Error::RedemptionNotFound)?;
+                Ok(serde_json::to_string(&request)?)
+            }
+            Query::GetLatestValuation { token_id } => {
+                let count = self.valuation_count.get(&token_id).await?.unwrap_or(0);
+                let report = match count.checked_sub(1) {
+                    Some(sequence) => self.valuations.get(&(token_id, sequence)).await?,
+                    None => None,
+                };
+                let window = self.valuation_staleness_micros.get().await?;
+                let now = self.runtime().system_time();
+                let latest = report.map(|report| {
+                    let age = now.micros().saturating_sub(report.timestamp.micros());
+                    LatestValuation {
+                        stale: window > 0 && age > window,
+                        report,
+                    }
+                });
+                Ok(serde_json::to_string(&latest)?)
+            }
+            Query::GetValuationHistory { token_id, offset, limit } => {
+                // Newest first
+                let count = self.valuation_count.get(&token_id).await?.unwrap_or(0);
+                let end = count.min(u64::from(offset) + u64::from(limit).min(MAX_PAGE_SIZE as u64));
+                let mut page: Vec<ValuationReport> = Vec::new();
+                for position in u64::from(offset)..end {
+                    let sequence = count - 1 - position;
+                    if let Some(report) = self.valuations.get(&(token_id, sequence)).await? {
+                        page.push(report);
+                    }
+                }
+                Ok(serde_json::to_string(&page)?)
+            }
+            Query::GetUser { token_id } => {
+                if !self.assets.contains_key(&token_id).await? {
+                    return Err(Error::TokenNotFound);
+                }
+                let now = self.runtime().system_time();
+                let right = self
+                    .usage_rights
+                    .get(&token_id)
+                    .await?
+                    .filter(|right| right.expires > now);
+                Ok(serde_json::to_string(&right)?)
+            }
+            Query::GetDocuments { token_id } => {
+                let mut latest = Vec::new();
+                if let Some(versions) = self.document_versions.try_load_entry(&token_id).await? {
+                    versions
+                        .for_each_index_value(|name, version| {
+                            latest.push((name, version));
+                            Ok(())
+                        })
+                        .await?;
+                }
+                let mut documents = Vec::new();
+                for (name, version) in latest {
+                    if let Some(document) = self.documents.get(&(token_id, name, version)).await? {
+                        documents.push(document);
+                    }
+                }
+                Ok(serde_json::to_string(&documents)?)
+            }
+            Query::VerifyDocument {
+                token_id,
+                name,
+                content_hash,
+                version,
+            } => {
+                // Without a version, compare against the latest one
+                let version = match version {
+                    Some(version) => version,
+                    None => match self.document_versions.try_load_entry(&token_id).await? {
+                        Some(versions) => versions.get(&name).await?.ok_or(Error::DocumentNotFound)?,
+                        None => return Err(Error::DocumentNotFound),
+                    },
+                };
+                let document = self
+                    .documents
+                    .get(&(token_id, name, version))
+                    .await?
+                    .ok_or(Error::DocumentNotFound)?;
+                let verification = DocumentVerification {
+                    matches: document.content_hash == content_hash,
+                    document,
+                };
+                Ok(serde_json::to_string(&verification)?)
+            }
+        }
+    }
+}
+
+impl RwaState {
+    async fn ensure_admin(&self, caller: &AccountOwner) -> Result<(), Error> {
+        if *caller != self.admin.get().await? {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Fails if the asset is locked and must not change owner.
+    async fn ensure_transferable(&self, token_id: TokenId) -> Result<(), Error> {
+        let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+        if asset.lien.is_some() {
+            return Err(Error::Liened);
+        }
+        if self.fractions.contains_key(&token_id).await? {
+            return Err(Error::Fractionalized);
+        }
+        if let Some(request) = self.redemptions.get(&token_id).await? {
+            if request.status == RedemptionStatus::Pending {
+                return Err(Error::RedemptionPending);
+            }
+        }
+        Ok(())
+    }
+
+    async fn ensure_not_rented(&mut self, token_id: TokenId) -> Result<(), Error> {
+        let now = self.runtime().system_time();
+        if let Some(right) = self.usage_rights.get(&token_id).await? {
+            if right.expires > now {
+                return Err(Error::Rented);
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads an asset whose lien is held by `lender`.
+    async fn liened_asset(&self, token_id: TokenId, lender: ApplicationId) -> Result<Asset, Error> {
+        let asset = self.assets.get(&token_id).await?.ok_or(Error::TokenNotFound)?;
+        match &asset.lien {
+            Some(lien) if lien.lender == lender => Ok(asset),
+            Some(_) => Err(Error::Unauthorized),
+            None => Err(Error::NoLien),
+        }
+    }
+
+    async fn pending_redemption(&self, token_id: TokenId) -> Result<RedemptionRequest, Error> {
+        let request = self.redemptions.get(&token_id).await?.ok_or(Error::RedemptionNotFound)?;
+        if request.status != RedemptionStatus::Pending {
+            return Err(Error::RedemptionNotPending);
+        }
+        Ok(request)
+    }
+
+    /// Applies the asset's transfer rules, if any, to a transfer to `to`.
+    /// `holder_count` is the number of share holders after the transfer.
+    async fn check_compliance(
+        &mut self,
+        token_id: TokenId,
+        to: &AccountOwner,
+        holder_count: Option<u64>,
+    ) -> Result<(), Error> {
+        let Some(rules) = self.transfer_rules.get(&token_id).await? else {
+            return Ok(());
+        };
+        let now = self.runtime().system_time();
+        if let Some(lockup_until) = rules.lockup_until {
+            if now < lockup_until {
+                return Err(Error::LockedUp(lockup_until));
+            }
+        }
+        let identity = self.identities.get(to).await?.ok_or(Error::RecipientNotVerified)?;
+        if !identity.verified {
+            return Err(Error::RecipientNotVerified);
+        }
+        if identity.expires_at <= now {
+            return Err(Error::IdentityExpired(identity.expires_at));
+        }
+        if !rules.allowed_countries.is_empty() && !rules.allowed_countries.contains(&identity.country_code) {
+            return Err(Error::CountryNotAllowed(identity.country_code));
+        }
+        if let (Some(max_holders), Some(holder_count)) = (rules.max_holders, holder_count) {
+            if holder_count > max_holders {
+                return Err(Error::MaxHoldersExceeded(max_holders));
+            }
+        }
+        Ok(())
+    }
+
+    async fn is_approved_or_owner(
+        &self,
+        caller: &AccountOwner,
+        token_id: TokenId,
+        owner: &AccountOwner,
+    ) -> Result<bool, Error> {
+        if caller == owner {
+            return Ok(true);
+        }
+        if self.token_approvals.get(&token_id).await?.as_ref() == Some(caller) {
+            return Ok(true);
+        }
+        Ok(self.operators.contains(&(*owner, *caller)).await?)
+    }
+
+    /// Moves `token_id` from `from`'s token list to `to`'s.
+    async fn move_token(&mut self, token_id: TokenId, from: &AccountOwner, to: &AccountOwner) -> Result<(), Error> {
+        self.remove_owner_token(from, token_id).await?;
+        self.add_owner_token(to, token_id).await
+    }
+
+    async fn add_owner_token(&mut self, owner: &AccountOwner, token_id: TokenId) -> Result<(), Error> {
+        self.owner_tokens.load_entry_mut(owner).await?.insert(&token_id)?;
+        let count = self.owner_token_count.get(owner).await?.unwrap_or(0);
+        self.owner_token_count.insert(owner, count + 1)?;
+        Ok(())
+    }
+
+    /// Drops the owner's entry along with their last token, so emptied
+    /// accounts leave nothing behind.
+    async fn remove_owner_token(&mut self, owner: &AccountOwner, token_id: TokenId) -> Result<(), Error> {
+        match self.owner_token_count.get(owner).await?.unwrap_or(0) {
+            0 | 1 => {
+                self.owner_tokens.remove_entry(owner)?;
+                self.owner_token_count.remove(owner)?;
+            }
+            count => {
+                self.owner_tokens.load_entry_mut(owner).await?.remove(&token_id)?;
+                self.owner_token_count.insert(owner, count - 1)?;
+            }
         }
+        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Removes and returns a token's valuation reports, oldest first.
+    async fn remove_valuations(&mut self, token_id: TokenId) -> Result<Vec<ValuationReport>, Error> {
+        let count = self.valuation_count.get(&token_id).await?.unwrap_or(0);
+        let mut reports = Vec::new();
+        for sequence in 0..count {
+            if let Some(report) = self.valuations.get(&(token_id, sequence)).await? {
+                reports.push(report);
+            }
+            self.valuations.remove(&(token_id, sequence))?;
+        }
+        self.valuation_count.remove(&token_id)?;
+        Ok(reports)
+    }
+
+    /// Removes and returns every version of a token's documents.
+    async fn remove_documents(&mut self, token_id: TokenId) -> Result<Vec<AssetDocument>, Error> {
+        let mut latest = Vec::new();
+        if let Some(versions) = self.document_versions.try_load_entry(&token_id).await? {
+            versions
+                .for_each_index_value(|name, version| {
+                    latest.push((name, version));
+                    Ok(())
+                })
+                .await?;
+        }
+        let mut documents = Vec::new();
+        for (name, latest_version) in latest {
+            for version in 1..=latest_version {
+                let key = (token_id, name.clone(), version);
+                if let Some(document) = self.documents.get(&key).await? {
+                    documents.push(document);
+                }
+                self.documents.remove(&key)?;
+            }
+        }
+        self.document_versions.remove_entry(&token_id)?;
+        Ok(documents)
+    }
+}
+
+fn is_uppercase_code(code: &str, len: usize) -> bool {
+    code.len() == len && code.bytes().all(|byte| byte.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> AssetMetadata {
+        AssetMetadata {
+            asset_class: AssetClass::RealEstate,
+            jurisdiction: "DE".to_string(),
+            valuation: 1_000_000,
+            currency: "EUR".to_string(),
+            legal_identifier: "Grundbuch Berlin-Mitte 1234".to_string(),
+            attributes: BTreeMap::from([("floor_area_m2".to_string(), "120".to_string())]),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_metadata() {
+        assert!(metadata().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_codes() {
+        for jurisdiction in ["de", "DEU", "D1", ""] {
+            let metadata = AssetMetadata { jurisdiction: jurisdiction.to_string(), ..metadata() };
+            assert!(metadata.validate().is_err(), "{jurisdiction}");
+        }
+        for currency in ["eur", "EU", "EURO", "E1R"] {
+            let metadata = AssetMetadata { currency: currency.to_string(), ..metadata() };
+            assert!(metadata.validate().is_err(), "{currency}");
+        }
+    }
+
+    #[test]
+    fn rejects_zero_valuation_and_bad_legal_identifier() {
+        assert!(AssetMetadata { valuation: 0, ..metadata() }.validate().is_err());
+        assert!(AssetMetadata { legal_identifier: String::new(), ..metadata() }.validate().is_err());
+        let legal_identifier = "x".repeat(MAX_LEGAL_IDENTIFIER_LEN + 1);
+        assert!(AssetMetadata { legal_identifier, ..metadata() }.validate().is_err());
+    }
+
+    #[test]
+    fn enforces_attribute_limits() {
+        let attributes = (0..=MAX_ATTRIBUTES).map(|i| (i.to_string(), String::new())).collect();
+        assert!(AssetMetadata { attributes, ..metadata() }.validate().is_err());
+
+        let attributes = BTreeMap::from([(String::new(), "value".to_string())]);
+        assert!(AssetMetadata { attributes, ..metadata() }.validate().is_err());
+
+        let key = "k".repeat(MAX_ATTRIBUTE_KEY_LEN + 1);
+        let attributes = BTreeMap::from([(key, "value".to_string())]);
+        assert!(AssetMetadata { attributes, ..metadata() }.validate().is_err());
+
+        let value = "v".repeat(MAX_ATTRIBUTE_VALUE_LEN + 1);
+        let attributes = BTreeMap::from([("key".to_string(), value)]);
+        assert!(AssetMetadata { attributes, ..metadata() }.validate().is_err());
+
+        let attributes = (0..MAX_ATTRIBUTES).map(|i| (i.to_string(), String::new())).collect();
+        assert!(AssetMetadata { attributes, ..metadata() }.validate().is_ok());
+    }
+
+    fn royalty(basis_points: u16) -> Royalty {
+        Royalty {
+            receiver: AccountOwner::from(CryptoHash::test_hash("receiver")),
+            basis_points,
+        }
+    }
+
+    #[test]
+    fn royalty_amount_rounds_down() {
+        assert_eq!(royalty(250).amount(0), 0);
+        assert_eq!(royalty(250).amount(39), 0);
+        assert_eq!(royalty(250).amount(40), 1);
+        assert_eq!(royalty(250).amount(1_000_000), 25_000);
+        assert_eq!(royalty(10_000).amount(12_345), 12_345);
+        assert_eq!(royalty(0).amount(12_345), 0);
+    }
+
+    #[test]
+    fn royalty_amount_does_not_overflow() {
+        assert_eq!(royalty(10_000).amount(u128::MAX), u128::MAX);
+        assert_eq!(royalty(5_000).amount(u128::MAX), u128::MAX / 2);
+    }
+
+    #[test]
+    fn royalty_rejects_more_than_full_price() {
+        assert!(royalty(10_000).validate().is_ok());
+        assert!(royalty(10_001).validate().is_err());
+    }
+}
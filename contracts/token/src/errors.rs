@@ -8,6 +8,90 @@ pub enum Error {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    #[error("Transfer amount must be positive")]
+    ZeroAmount,
+
+    #[error("Transfer memo is too long")]
+    MemoTooLong,
+
+    #[error("Too many transfers in batch")]
+    BatchTooLarge,
+
+    #[error("Airdrop not found")]
+    AirdropNotFound,
+
+    #[error("Airdrop already claimed")]
+    AlreadyClaimed,
+
+    #[error("Invalid Merkle proof")]
+    InvalidProof,
+
+    #[error("Airdrop funds exhausted")]
+    AirdropExhausted,
+
+    #[error("Snapshot not found")]
+    SnapshotNotFound,
+
+    #[error("Flash mint was not repaid with its fee")]
+    FlashMintNotRepaid,
+
+    #[error("Insufficient allowance")]
+    InsufficientAllowance,
+
+    #[error("Permit deadline has passed")]
+    PermitExpired,
+
+    #[error("Invalid permit nonce")]
+    InvalidNonce,
+
+    #[error("Invalid permit signature")]
+    InvalidSignature,
+
+    #[error("Token is paused")]
+    Paused,
+
+    #[error("Account is frozen")]
+    AccountFrozen,
+
+    #[error("Mint would exceed the maximum supply")]
+    SupplyCapExceeded,
+
+    #[error("Mint would exceed the current epoch budget")]
+    EpochBudgetExceeded,
+
+    #[error("Vesting grant not found")]
+    GrantNotFound,
+
+    #[error("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+
+    #[error("Vesting grant is not revocable")]
+    GrantNotRevocable,
+
+    #[error("Vesting grant already revoked")]
+    GrantRevoked,
+
+    #[error("Nothing to release")]
+    NothingToRelease,
+
+    #[error("Insufficient staked balance")]
+    InsufficientStake,
+
+    #[error("Too many pending unbondings")]
+    TooManyUnbondings,
+
+    #[error("No unbonded tokens to withdraw")]
+    NothingToWithdraw,
+
+    #[error("No stakers to distribute rewards to")]
+    NoStakers,
+
+    #[error("No rewards to claim")]
+    NothingToClaim,
+
     #[error("View error: {0}")]
     ViewError(#[from] linera_views::views::ViewError),
 
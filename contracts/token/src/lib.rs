@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 
 pub mod errors;
+pub mod merkle;
 pub mod operations;
 pub mod state;
 
@@ -0,0 +1,82 @@
+use linera_sdk::base::{AccountOwner, BcsHashable, CryptoHash};
+use serde::{Deserialize, Serialize};
+
+/// Leaf of an airdrop Merkle tree: recipient `account` may claim `amount`
+/// using claim slot `index`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AirdropLeaf {
+    pub index: u64,
+    pub account: AccountOwner,
+    pub amount: u128,
+}
+
+impl BcsHashable for AirdropLeaf {}
+
+#[derive(Serialize, Deserialize)]
+struct MerkleNode(CryptoHash, CryptoHash);
+
+impl BcsHashable for MerkleNode {}
+
+/// Children are hashed in sorted order, so proofs carry no position bits.
+pub fn hash_pair(a: CryptoHash, b: CryptoHash) -> CryptoHash {
+    if a <= b {
+        CryptoHash::new(&MerkleNode(a, b))
+    } else {
+        CryptoHash::new(&MerkleNode(b, a))
+    }
+}
+
+pub fn verify_proof(root: CryptoHash, leaf: &AirdropLeaf, proof: &[CryptoHash]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(CryptoHash::new(leaf), |node, sibling| hash_pair(node, *sibling));
+    computed == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(index: u64, name: &str, amount: u128) -> AirdropLeaf {
+        AirdropLeaf {
+            index,
+            account: AccountOwner::from(CryptoHash::test_hash(name)),
+            amount,
+        }
+    }
+
+    /// Root and leaves of a three-leaf tree: ((a, b), c).
+    fn tree() -> (CryptoHash, [AirdropLeaf; 3]) {
+        let leaves = [leaf(0, "alice", 100), leaf(1, "bob", 200), leaf(2, "carol", 300)];
+        let ab = hash_pair(CryptoHash::new(&leaves[0]), CryptoHash::new(&leaves[1]));
+        let root = hash_pair(ab, CryptoHash::new(&leaves[2]));
+        (root, leaves)
+    }
+
+    #[test]
+    fn hash_pair_is_order_independent() {
+        let a = CryptoHash::test_hash("a");
+        let b = CryptoHash::test_hash("b");
+        assert_eq!(hash_pair(a, b), hash_pair(b, a));
+    }
+
+    #[test]
+    fn accepts_valid_proofs() {
+        let (root, [a, b, c]) = tree();
+        let ab = hash_pair(CryptoHash::new(&a), CryptoHash::new(&b));
+        assert!(verify_proof(root, &a, &[CryptoHash::new(&b), CryptoHash::new(&c)]));
+        assert!(verify_proof(root, &b, &[CryptoHash::new(&a), CryptoHash::new(&c)]));
+        assert!(verify_proof(root, &c, &[ab]));
+    }
+
+    #[test]
+    fn rejects_tampered_leaves_and_proofs() {
+        let (root, [a, b, c]) = tree();
+        let proof = [CryptoHash::new(&b), CryptoHash::new(&c)];
+        assert!(!verify_proof(root, &leaf(0, "alice", 101), &proof));
+        assert!(!verify_proof(root, &leaf(0, "mallory", 100), &proof));
+        assert!(!verify_proof(root, &leaf(5, "alice", 100), &proof));
+        assert!(!verify_proof(root, &a, &proof[..1]));
+        assert!(!verify_proof(root, &a, &[]));
+    }
+}
@@ -1,4 +1,6 @@
-use linera_sdk::base::AccountOwner;
+use linera_sdk::base::{
+    AccountOwner, ApplicationId, CryptoHash, PublicKey, Signature, Timestamp,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -6,6 +8,7 @@ pub enum Operation {
     Transfer {
         to: AccountOwner,
         amount: u128,
+        memo: Option<String>,
     },
     Mint {
         to: AccountOwner,
@@ -15,10 +18,146 @@ pub enum Operation {
         from: AccountOwner,
         amount: u128,
     },
+    CreateVestingGrant {
+        beneficiary: AccountOwner,
+        amount: u128,
+        start: Timestamp,
+        cliff_micros: u64,
+        duration_micros: u64,
+        revocable: bool,
+    },
+    Release {
+        grant_id: u64,
+    },
+    RevokeVestingGrant {
+        grant_id: u64,
+    },
+    SetRewardDistributor {
+        distributor: AccountOwner,
+    },
+    Stake {
+        amount: u128,
+    },
+    Unstake {
+        amount: u128,
+    },
+    WithdrawUnbonded,
+    FundRewards {
+        amount: u128,
+    },
+    ClaimRewards,
+    SetPauser {
+        pauser: AccountOwner,
+    },
+    Pause,
+    Unpause,
+    FreezeAccount {
+        owner: AccountOwner,
+    },
+    UnfreezeAccount {
+        owner: AccountOwner,
+    },
+    Approve {
+        spender: AccountOwner,
+        amount: u128,
+    },
+    TransferFrom {
+        from: AccountOwner,
+        to: AccountOwner,
+        amount: u128,
+    },
+    Permit {
+        owner: AccountOwner,
+        public_key: PublicKey,
+        spender: AccountOwner,
+        amount: u128,
+        nonce: u64,
+        deadline: Timestamp,
+        signature: Signature,
+    },
+    BatchTransfer {
+        transfers: Vec<(AccountOwner, u128)>,
+    },
+    CreateAirdrop {
+        merkle_root: CryptoHash,
+        amount: u128,
+    },
+    ClaimAirdrop {
+        airdrop_id: u64,
+        index: u64,
+        amount: u128,
+        proof: Vec<CryptoHash>,
+    },
+    CreateSnapshot,
+    SetMinterApplication {
+        application_id: ApplicationId,
+        authorized: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Query {
     GetBalance { owner: AccountOwner },
     GetTotalSupply,
+    GetRemainingMintable,
+    GetEpochBudget,
+    GetVestingGrant { grant_id: u64 },
+    GetBeneficiaryGrants { beneficiary: AccountOwner },
+    GetStake { owner: AccountOwner },
+    GetStakingInfo,
+    GetPauseStatus { owner: AccountOwner },
+    GetAllowance { owner: AccountOwner, spender: AccountOwner },
+    GetPermitNonce { owner: AccountOwner },
+    /// Newest transfers first.
+    GetTransferHistory { owner: AccountOwner, offset: u32, limit: u32 },
+    GetAirdrop { airdrop_id: u64 },
+    IsAirdropClaimed { airdrop_id: u64, index: u64 },
+    GetHolderCount,
+    GetHolders { offset: u32, limit: u32 },
+    GetTopHolders { limit: u32 },
+    GetCurrentSnapshotId,
+    GetBalanceAt { owner: AccountOwner, snapshot_id: u64 },
+    GetTotalSupplyAt { snapshot_id: u64 },
+    /// Debug check that balances plus contract-held funds equal the supply.
+    CheckSupplyInvariant,
+    GetFlashMintFee { amount: u128 },
+    IsMinterApplication { application_id: ApplicationId },
+}
+
+/// Calls other SnapCraft applications (DAO, optimizer, RWA) make into the
+/// token contract.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ApplicationCall {
+    Balance {
+        owner: AccountOwner,
+    },
+    /// `owner` must be the calling application or the authenticated signer.
+    Transfer {
+        owner: AccountOwner,
+        to: AccountOwner,
+        amount: u128,
+    },
+    /// Spends an allowance granted to the calling application's account.
+    TransferFrom {
+        from: AccountOwner,
+        to: AccountOwner,
+        amount: u128,
+    },
+    /// Only for applications registered with `Operation::SetMinterApplication`.
+    Mint {
+        to: AccountOwner,
+        amount: u128,
+    },
+    /// Mints `amount` to the calling application, which may spend it through
+    /// further calls. `amount` plus the fee is owed until `RepayFlashMint`;
+    /// the transaction fails if it is still owed when it finishes.
+    FlashMint { amount: u128 },
+    /// Burns everything the calling application owes from its account.
+    RepayFlashMint,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CallResponse {
+    Ok,
+    Balance(u128),
 }
\ No newline at end of file
@@ -1,22 +1,241 @@
 use linera_sdk::{
-    base::{AccountOwner, ContractRuntime, ServiceRuntime},
-    views::{MapView, RegisterView, ViewStorageContext},
+    base::{
+        AccountOwner, ApplicationId, BcsSignable, ContractRuntime, CryptoHash, PublicKey,
+        ServiceRuntime, Timestamp,
+    },
+    views::{MapView, RegisterView, SetView, ViewStorageContext},
 };
 use linera_views::views::ViewError;
 use serde::{Deserialize, Serialize};
 
 use super::errors::Error;
-use super::operations::{Operation, Query};
+use super::merkle::{self, AirdropLeaf};
+use super::operations::{ApplicationCall, CallResponse, Operation, Query};
 
 const NAME: &str = "C0mrad";
 const SYMBOL: &str = "CMR";
 const DECIMALS: u8 = 18;
 const INITIAL_SUPPLY: u128 = 1_000_000 * 10u128.pow(DECIMALS as u32);
+const MAX_SUPPLY: u128 = 100_000_000 * 10u128.pow(DECIMALS as u32);
+
+// Emission schedule: each epoch may mint up to its budget, and the budget
+// halves every EPOCHS_PER_HALVING epochs.
+const EPOCH_DURATION_MICROS: u64 = 7 * 24 * 60 * 60 * 1_000_000; // 1 week
+const INITIAL_EPOCH_BUDGET: u128 = 500_000 * 10u128.pow(DECIMALS as u32);
+const EPOCHS_PER_HALVING: u64 = 52;
+
+const UNBONDING_PERIOD_MICROS: u64 = 7 * 24 * 60 * 60 * 1_000_000; // 1 week
+const MAX_UNBONDING_ENTRIES: usize = 16;
+// Scale of `reward_per_token`, keeps precision when rewards are small
+// relative to the staked amount
+const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+const MAX_MEMO_LEN: usize = 256;
+const MAX_HISTORY_LEN: u64 = 100;
+const MAX_PAGE_SIZE: usize = 50;
+const MAX_BATCH_SIZE: usize = 100;
+const MAX_TOP_HOLDERS: usize = 100;
+
+const FLASH_MINT_FEE_BPS: u128 = 9; // 0.09%
+const BPS_DENOMINATOR: u128 = 10_000;
+// Claimed flags are packed 128 per bitmap word
+const CLAIM_BITMAP_WORD_BITS: u64 = u128::BITS as u64;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EpochBudget {
+    pub epoch: u64,
+    pub budget: u128,
+    pub minted: u128,
+    pub remaining: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingGrant {
+    pub beneficiary: AccountOwner,
+    pub total: u128,
+    pub released: u128,
+    pub start: Timestamp,
+    pub cliff_micros: u64,
+    pub duration_micros: u64,
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+impl VestingGrant {
+    /// Amount vested at `now`. A revoked grant keeps whatever had vested at
+    /// revocation time, which is stored in `total`.
+    pub fn vested_at(&self, now: Timestamp) -> u128 {
+        if self.revoked {
+            return self.total;
+        }
+        let elapsed = now.micros().saturating_sub(self.start.micros());
+        if elapsed < self.cliff_micros {
+            return 0;
+        }
+        if elapsed >= self.duration_micros {
+            return self.total;
+        }
+        // Split the multiplication so it cannot overflow for large grants
+        let (elapsed, duration) = (elapsed as u128, self.duration_micros as u128);
+        self.total / duration * elapsed + self.total % duration * elapsed / duration
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnbondingEntry {
+    pub amount: u128,
+    pub release_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StakeInfo {
+    pub staked: u128,
+    /// Value of `reward_per_token` when rewards were last settled.
+    pub reward_debt: u128,
+    pub pending_rewards: u128,
+    pub unbonding: Vec<UnbondingEntry>,
+}
+
+impl StakeInfo {
+    /// Moves rewards accrued since the last settlement into `pending_rewards`.
+    pub fn settle(&mut self, reward_per_token: u128) -> Result<(), Error> {
+        let delta = checked_sub(reward_per_token, self.reward_debt)?;
+        let scaled = self.staked.checked_mul(delta).ok_or(Error::ArithmeticOverflow)?;
+        let accrued = scaled / REWARD_PRECISION;
+        self.pending_rewards = checked_add(self.pending_rewards, accrued)?;
+        self.reward_debt = reward_per_token;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StakingInfo {
+    pub total_staked: u128,
+    pub total_unbonding: u128,
+    pub reward_pool: u128,
+    pub reward_per_token: u128,
+    pub distributor: Option<AccountOwner>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PauseStatus {
+    pub paused: bool,
+    pub frozen: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TransferRecord {
+    pub counterparty: AccountOwner,
+    pub amount: u128,
+    pub direction: TransferDirection,
+    pub timestamp: Timestamp,
+    pub memo: Option<String>,
+}
+
+/// Breakdown of where the supply sits. Tokens held by the contract itself
+/// (escrows, stakes, reward pool) are not in `balances`, so `holds` compares
+/// their sum with `total_supply`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SupplyInvariant {
+    pub sum_of_balances: u128,
+    pub vesting_escrow: u128,
+    pub total_staked: u128,
+    pub total_unbonding: u128,
+    pub reward_pool: u128,
+    pub airdrop_escrow: u128,
+    pub total_supply: u128,
+    pub holds: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Holder {
+    pub owner: AccountOwner,
+    pub balance: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Airdrop {
+    pub creator: AccountOwner,
+    pub merkle_root: CryptoHash,
+    pub total_amount: u128,
+    pub claimed_amount: u128,
+}
+
+/// Approval an owner signs off-line so a keeper can submit it with
+/// `Operation::Permit`. The application id binds the signature to this
+/// token instance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PermitMessage {
+    pub application_id: ApplicationId,
+    pub owner: AccountOwner,
+    pub spender: AccountOwner,
+    pub amount: u128,
+    pub nonce: u64,
+    pub deadline: Timestamp,
+}
+
+impl BcsSignable for PermitMessage {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingStatus {
+    pub grant: VestingGrant,
+    pub vested: u128,
+    pub unvested: u128,
+    pub releasable: u128,
+}
 
 #[derive(linera_sdk::views::ViewStorage)]
 pub struct TokenState {
+    /// Only accounts with a non-zero balance have an entry.
     pub balances: MapView<AccountOwner, u128>,
+    pub holder_count: RegisterView<u64>,
+    /// Holders ordered by balance, largest first. See `rank_key`.
+    pub holders_by_balance: SetView<([u8; 16], AccountOwner)>,
     pub total_supply: RegisterView<u128>,
+    pub admin: RegisterView<AccountOwner>,
+    pub emission_start: RegisterView<Timestamp>,
+    pub emission_epoch: RegisterView<u64>,
+    pub epoch_minted: RegisterView<u128>,
+    pub next_grant_id: RegisterView<u64>,
+    pub vesting_grants: MapView<u64, VestingGrant>,
+    pub beneficiary_grants: MapView<AccountOwner, Vec<u64>>,
+    pub vesting_escrow: RegisterView<u128>,
+    pub reward_distributor: RegisterView<Option<AccountOwner>>,
+    pub stakes: MapView<AccountOwner, StakeInfo>,
+    pub total_staked: RegisterView<u128>,
+    pub total_unbonding: RegisterView<u128>,
+    pub reward_per_token: RegisterView<u128>,
+    pub reward_pool: RegisterView<u128>,
+    pub pauser: RegisterView<Option<AccountOwner>>,
+    pub paused: RegisterView<bool>,
+    pub frozen: SetView<AccountOwner>,
+    pub allowances: MapView<(AccountOwner, AccountOwner), u128>,
+    pub permit_nonces: MapView<AccountOwner, u64>,
+    /// Ring buffer of the last `MAX_HISTORY_LEN` transfers per account,
+    /// keyed by (account, sequence number % `MAX_HISTORY_LEN`).
+    pub transfer_history: MapView<(AccountOwner, u64), TransferRecord>,
+    /// Number of transfers ever recorded per account.
+    pub transfer_count: MapView<AccountOwner, u64>,
+    pub next_airdrop_id: RegisterView<u64>,
+    pub airdrops: MapView<u64, Airdrop>,
+    /// Claimed bitmap keyed by (airdrop id, word index).
+    pub airdrop_claimed: MapView<(u64, u64), u128>,
+    pub airdrop_escrow: RegisterView<u128>,
+    pub current_snapshot_id: RegisterView<u64>,
+    /// The balance an account held when a snapshot was taken, written lazily
+    /// on the account's first change after it.
+    pub balance_checkpoints: MapView<(AccountOwner, u64), u128>,
+    pub supply_snapshots: MapView<u64, u128>,
+    /// Applications allowed to mint through `ApplicationCall::Mint`.
+    pub minter_applications: SetView<ApplicationId>,
+    /// Flash mint amount plus fee owed per borrower; empty between transactions.
+    pub flash_loans: MapView<ApplicationId, u128>,
     // For voting power, add: voting_power: MapView<AccountOwner, u128>,
 }
 
@@ -24,64 +243,499 @@ pub struct TokenState {
 impl Contract for TokenState {
     type Error = Error;
     type Operation = Operation;
-    type ApplicationCall = ();
+    type ApplicationCall = ApplicationCall;
+    type Response = CallResponse;
     type SessionState = ();
+    type InstantiationArgument = ();
 
     async fn new(runtime: ContractRuntime<Self>) -> Result<Self, Self::Error> {
         let context = ViewStorageContext::from(runtime.root_view_storage_context());
-        let mut state = Self {
+        Ok(Self {
             balances: MapView::load(context.clone().sub("balances"))?,
-            total_supply: RegisterView::load(context.sub("total_supply"))?,
-        };
-        // Initial mint to creator
-        let creator = runtime.authenticated_signer();
-        state.total_supply.set(INITIAL_SUPPLY);
-        state.balances.insert(&creator, INITIAL_SUPPLY)?;
-        Ok(state)
+            holder_count: RegisterView::load(context.clone().sub("holder_count"))?,
+            holders_by_balance: SetView::load(context.clone().sub("holders_by_balance"))?,
+            total_supply: RegisterView::load(context.clone().sub("total_supply"))?,
+            admin: RegisterView::load(context.clone().sub("admin"))?,
+            emission_start: RegisterView::load(context.clone().sub("emission_start"))?,
+            emission_epoch: RegisterView::load(context.clone().sub("emission_epoch"))?,
+            epoch_minted: RegisterView::load(context.clone().sub("epoch_minted"))?,
+            next_grant_id: RegisterView::load(context.clone().sub("next_grant_id"))?,
+            vesting_grants: MapView::load(context.clone().sub("vesting_grants"))?,
+            beneficiary_grants: MapView::load(context.clone().sub("beneficiary_grants"))?,
+            vesting_escrow: RegisterView::load(context.clone().sub("vesting_escrow"))?,
+            reward_distributor: RegisterView::load(context.clone().sub("reward_distributor"))?,
+            stakes: MapView::load(context.clone().sub("stakes"))?,
+            total_staked: RegisterView::load(context.clone().sub("total_staked"))?,
+            total_unbonding: RegisterView::load(context.clone().sub("total_unbonding"))?,
+            reward_per_token: RegisterView::load(context.clone().sub("reward_per_token"))?,
+            reward_pool: RegisterView::load(context.clone().sub("reward_pool"))?,
+            pauser: RegisterView::load(context.clone().sub("pauser"))?,
+            paused: RegisterView::load(context.clone().sub("paused"))?,
+            frozen: SetView::load(context.clone().sub("frozen"))?,
+            allowances: MapView::load(context.clone().sub("allowances"))?,
+            permit_nonces: MapView::load(context.clone().sub("permit_nonces"))?,
+            transfer_history: MapView::load(context.clone().sub("transfer_history"))?,
+            transfer_count: MapView::load(context.clone().sub("transfer_count"))?,
+            next_airdrop_id: RegisterView::load(context.clone().sub("next_airdrop_id"))?,
+            airdrops: MapView::load(context.clone().sub("airdrops"))?,
+            airdrop_claimed: MapView::load(context.clone().sub("airdrop_claimed"))?,
+            airdrop_escrow: RegisterView::load(context.clone().sub("airdrop_escrow"))?,
+            current_snapshot_id: RegisterView::load(context.clone().sub("current_snapshot_id"))?,
+            balance_checkpoints: MapView::load(context.clone().sub("balance_checkpoints"))?,
+            supply_snapshots: MapView::load(context.clone().sub("supply_snapshots"))?,
+            minter_applications: SetView::load(context.clone().sub("minter_applications"))?,
+            flash_loans: MapView::load(context.sub("flash_loans"))?,
+        })
+    }
+
+    async fn instantiate(&mut self, _argument: ()) -> Result<(), Self::Error> {
+        // Initial mint to creator, who also administers emissions
+        let creator = self.runtime().authenticated_signer();
+        self.admin.set(creator);
+        self.emission_start.set(self.runtime().system_time());
+        self.total_supply.set(INITIAL_SUPPLY);
+        self.credit(&creator, INITIAL_SUPPLY).await?;
+        Ok(())
     }
 
     async fn execute_operation(&mut self, operation: Operation) -> Result<(), Self::Error> {
         let caller = self.runtime().authenticated_signer();
 
         match operation {
-            Operation::Transfer { to, amount } => {
-                let mut from_balance = self.balances.get(&caller).await?.unwrap_or(0);
-                if from_balance < amount {
-                    return Err(Error::InsufficientBalance);
-                }
-                from_balance -= amount;
-                self.balances.insert(&caller, from_balance)?;
-
-                let mut to_balance = self.balances.get(&to).await?.unwrap_or(0);
-                to_balance += amount;
-                self.balances.insert(&to, to_balance)?;
+            Operation::Transfer { to, amount, memo } => {
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&caller).await?;
+                self.ensure_not_frozen(&to).await?;
+                self.transfer(caller, to, amount, memo).await?;
             }
             Operation::Mint { to, amount } => {
-                // Restrict to caller == admin in production
-                let mut total = self.total_supply.get().await?;
-                total += amount;
-                self.total_supply.set(total);
-
-                let mut balance = self.balances.get(&to).await?.unwrap_or(0);
-                balance += amount;
-                self.balances.insert(&to, balance)?;
+                if caller != self.admin.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                self.mint(to, amount).await?;
             }
             Operation::Burn { from, amount } => {
                 if from != caller {
                     return Err(Error::Unauthorized);
                 }
-                let mut balance = self.balances.get(&from).await?.unwrap_or(0);
-                if balance < amount {
-                    return Err(Error::InsufficientBalance);
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&from).await?;
+                self.debit(&from, amount).await?;
+
+                let total = self.total_supply.get().await?;
+                self.total_supply.set(checked_sub(total, amount)?);
+            }
+            Operation::CreateVestingGrant {
+                beneficiary,
+                amount,
+                start,
+                cliff_micros,
+                duration_micros,
+                revocable,
+            } => {
+                if caller != self.admin.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                if duration_micros == 0 || cliff_micros > duration_micros {
+                    return Err(Error::InvalidVestingSchedule);
                 }
-                balance -= amount;
-                self.balances.insert(&from, balance)?;
+                self.debit(&caller, amount).await?;
+                let escrow = self.vesting_escrow.get().await?;
+                self.vesting_escrow.set(checked_add(escrow, amount)?);
+
+                let mut id = self.next_grant_id.get().await?;
+                id += 1;
+                self.next_grant_id.set(id);
+
+                let grant = VestingGrant {
+                    beneficiary,
+                    total: amount,
+                    released: 0,
+                    start,
+                    cliff_micros,
+                    duration_micros,
+                    revocable,
+                    revoked: false,
+                };
+                self.vesting_grants.insert(&id, grant)?;
 
-                let mut total = self.total_supply.get().await?;
-                total -= amount;
-                self.total_supply.set(total);
+                let mut grants = self.beneficiary_grants.get(&beneficiary).await?.unwrap_or_default();
+                grants.push(id);
+                self.beneficiary_grants.insert(&beneficiary, grants)?;
+            }
+            Operation::Release { grant_id } => {
+                let mut grant = self.vesting_grants.get(&grant_id).await?.ok_or(Error::GrantNotFound)?;
+                if grant.beneficiary != caller {
+                    return Err(Error::Unauthorized);
+                }
+                let now = self.runtime().system_time();
+                let releasable = checked_sub(grant.vested_at(now), grant.released)?;
+                if releasable == 0 {
+                    return Err(Error::NothingToRelease);
+                }
+                grant.released = checked_add(grant.released, releasable)?;
+                self.vesting_grants.insert(&grant_id, grant)?;
+
+                let escrow = self.vesting_escrow.get().await?;
+                self.vesting_escrow.set(checked_sub(escrow, releasable)?);
+                self.credit(&caller, releasable).await?;
+            }
+            Operation::RevokeVestingGrant { grant_id } => {
+                let admin = self.admin.get().await?;
+                if caller != admin {
+                    return Err(Error::Unauthorized);
+                }
+                let mut grant = self.vesting_grants.get(&grant_id).await?.ok_or(Error::GrantNotFound)?;
+                if !grant.revocable {
+                    return Err(Error::GrantNotRevocable);
+                }
+                if grant.revoked {
+                    return Err(Error::GrantRevoked);
+                }
+                // Vested tokens stay releasable by the beneficiary, the rest
+                // goes back to the admin
+                let now = self.runtime().system_time();
+                let vested = grant.vested_at(now);
+                let unvested = checked_sub(grant.total, vested)?;
+                grant.total = vested;
+                grant.revoked = true;
+                self.vesting_grants.insert(&grant_id, grant)?;
+
+                let escrow = self.vesting_escrow.get().await?;
+                self.vesting_escrow.set(checked_sub(escrow, unvested)?);
+                self.credit(&admin, unvested).await?;
+            }
+            Operation::SetRewardDistributor { distributor } => {
+                if caller != self.admin.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                self.reward_distributor.set(Some(distributor));
+            }
+            Operation::Stake { amount } => {
+                self.debit(&caller, amount).await?;
+
+                let reward_per_token = self.reward_per_token.get().await?;
+                let mut stake = self.stakes.get(&caller).await?.unwrap_or_default();
+                stake.settle(reward_per_token)?;
+                stake.staked = checked_add(stake.staked, amount)?;
+                self.stakes.insert(&caller, stake)?;
+
+                let total = self.total_staked.get().await?;
+                self.total_staked.set(checked_add(total, amount)?);
+            }
+            Operation::Unstake { amount } => {
+                let reward_per_token = self.reward_per_token.get().await?;
+                let mut stake = self.stakes.get(&caller).await?.unwrap_or_default();
+                if stake.staked < amount {
+                    return Err(Error::InsufficientStake);
+                }
+                if stake.unbonding.len() >= MAX_UNBONDING_ENTRIES {
+                    return Err(Error::TooManyUnbondings);
+                }
+                stake.settle(reward_per_token)?;
+                stake.staked = checked_sub(stake.staked, amount)?;
+                let now = self.runtime().system_time();
+                stake.unbonding.push(UnbondingEntry {
+                    amount,
+                    release_at: Timestamp::from(now.micros().saturating_add(UNBONDING_PERIOD_MICROS)),
+                });
+                self.stakes.insert(&caller, stake)?;
+
+                let total = self.total_staked.get().await?;
+                self.total_staked.set(checked_sub(total, amount)?);
+                let unbonding = self.total_unbonding.get().await?;
+                self.total_unbonding.set(checked_add(unbonding, amount)?);
+            }
+            Operation::WithdrawUnbonded => {
+                let mut stake = self.stakes.get(&caller).await?.unwrap_or_default();
+                let now = self.runtime().system_time();
+                let (ready, waiting): (Vec<_>, Vec<_>) = stake
+                    .unbonding
+                    .into_iter()
+                    .partition(|entry| entry.release_at <= now);
+                let amount = ready
+                    .iter()
+                    .try_fold(0, |sum, entry| checked_add(sum, entry.amount))?;
+                if amount == 0 {
+                    return Err(Error::NothingToWithdraw);
+                }
+                stake.unbonding = waiting;
+                self.stakes.insert(&caller, stake)?;
+
+                let unbonding = self.total_unbonding.get().await?;
+                self.total_unbonding.set(checked_sub(unbonding, amount)?);
+                self.credit(&caller, amount).await?;
+            }
+            Operation::FundRewards { amount } => {
+                if Some(caller) != self.reward_distributor.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                let total_staked = self.total_staked.get().await?;
+                if total_staked == 0 {
+                    return Err(Error::NoStakers);
+                }
+                self.debit(&caller, amount).await?;
+
+                let pool = self.reward_pool.get().await?;
+                self.reward_pool.set(checked_add(pool, amount)?);
+                let scaled = amount.checked_mul(REWARD_PRECISION).ok_or(Error::ArithmeticOverflow)?;
+                let increment = scaled / total_staked;
+                let reward_per_token = self.reward_per_token.get().await?;
+                self.reward_per_token.set(checked_add(reward_per_token, increment)?);
+            }
+            Operation::ClaimRewards => {
+                let reward_per_token = self.reward_per_token.get().await?;
+                let mut stake = self.stakes.get(&caller).await?.unwrap_or_default();
+                stake.settle(reward_per_token)?;
+                let reward = stake.pending_rewards;
+                if reward == 0 {
+                    return Err(Error::NothingToClaim);
+                }
+                stake.pending_rewards = 0;
+                self.stakes.insert(&caller, stake)?;
+
+                let pool = self.reward_pool.get().await?;
+                self.reward_pool.set(checked_sub(pool, reward)?);
+                self.credit(&caller, reward).await?;
+            }
+            Operation::SetPauser { pauser } => {
+                if caller != self.admin.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                self.pauser.set(Some(pauser));
+            }
+            Operation::Pause => {
+                self.ensure_pauser(&caller).await?;
+                self.paused.set(true);
+            }
+            Operation::Unpause => {
+                self.ensure_pauser(&caller).await?;
+                self.paused.set(false);
+            }
+            Operation::FreezeAccount { owner } => {
+                self.ensure_pauser(&caller).await?;
+                self.frozen.insert(&owner)?;
+            }
+            Operation::UnfreezeAccount { owner } => {
+                self.ensure_pauser(&caller).await?;
+                self.frozen.remove(&owner)?;
+            }
+            Operation::Approve { spender, amount } => {
+                self.allowances.insert(&(caller, spender), amount)?;
+            }
+            Operation::TransferFrom { from, to, amount } => {
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&caller).await?;
+                self.ensure_not_frozen(&from).await?;
+                self.ensure_not_frozen(&to).await?;
+                self.spend_allowance(from, caller, amount).await?;
+                self.transfer(from, to, amount, None).await?;
+            }
+            Operation::Permit {
+                owner,
+                public_key,
+                spender,
+                amount,
+                nonce,
+                deadline,
+                signature,
+            } => {
+                if self.runtime().system_time() > deadline {
+                    return Err(Error::PermitExpired);
+                }
+                let expected_nonce = self.permit_nonces.get(&owner).await?.unwrap_or(0);
+                if nonce != expected_nonce {
+                    return Err(Error::InvalidNonce);
+                }
+                if AccountOwner::from(public_key) != owner {
+                    return Err(Error::InvalidSignature);
+                }
+                let message = PermitMessage {
+                    application_id: self.runtime().application_id(),
+                    owner,
+                    spender,
+                    amount,
+                    nonce,
+                    deadline,
+                };
+                signature
+                    .check(&message, public_key)
+                    .map_err(|_| Error::InvalidSignature)?;
+
+                self.permit_nonces.insert(&owner, nonce + 1)?;
+                self.allowances.insert(&(owner, spender), amount)?;
+            }
+            Operation::BatchTransfer { transfers } => {
+                if transfers.len() > MAX_BATCH_SIZE {
+                    return Err(Error::BatchTooLarge);
+                }
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&caller).await?;
+                for (to, amount) in transfers {
+                    self.ensure_not_frozen(&to).await?;
+                    self.transfer(caller, to, amount, None).await?;
+                }
+            }
+            Operation::CreateAirdrop { merkle_root, amount } => {
+                if caller != self.admin.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                self.debit(&caller, amount).await?;
+                let escrow = self.airdrop_escrow.get().await?;
+                self.airdrop_escrow.set(checked_add(escrow, amount)?);
+
+                let mut id = self.next_airdrop_id.get().await?;
+                id += 1;
+                self.next_airdrop_id.set(id);
+
+                let airdrop = Airdrop {
+                    creator: caller,
+                    merkle_root,
+                    total_amount: amount,
+                    claimed_amount: 0,
+                };
+                self.airdrops.insert(&id, airdrop)?;
+            }
+            Operation::ClaimAirdrop {
+                airdrop_id,
+                index,
+                amount,
+                proof,
+            } => {
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&caller).await?;
+                let mut airdrop = self.airdrops.get(&airdrop_id).await?.ok_or(Error::AirdropNotFound)?;
+
+                let (word_key, bit) = claim_slot(airdrop_id, index);
+                let word = self.airdrop_claimed.get(&word_key).await?.unwrap_or(0);
+                if word & bit != 0 {
+                    return Err(Error::AlreadyClaimed);
+                }
+
+                let leaf = AirdropLeaf {
+                    index,
+                    account: caller,
+                    amount,
+                };
+                if !merkle::verify_proof(airdrop.merkle_root, &leaf, &proof) {
+                    return Err(Error::InvalidProof);
+                }
+                if amount > checked_sub(airdrop.total_amount, airdrop.claimed_amount)? {
+                    return Err(Error::AirdropExhausted);
+                }
+
+                self.airdrop_claimed.insert(&word_key, word | bit)?;
+                airdrop.claimed_amount = checked_add(airdrop.claimed_amount, amount)?;
+                self.airdrops.insert(&airdrop_id, airdrop)?;
+
+                let escrow = self.airdrop_escrow.get().await?;
+                self.airdrop_escrow.set(checked_sub(escrow, amount)?);
+                self.credit(&caller, amount).await?;
+            }
+            Operation::CreateSnapshot => {
+                if caller != self.admin.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                let mut id = self.current_snapshot_id.get().await?;
+                id += 1;
+                self.current_snapshot_id.set(id);
+
+                let supply = self.total_supply.get().await?;
+                self.supply_snapshots.insert(&id, supply)?;
+            }
+            Operation::SetMinterApplication {
+                application_id,
+                authorized,
+            } => {
+                if caller != self.admin.get().await? {
+                    return Err(Error::Unauthorized);
+                }
+                if authorized {
+                    self.minter_applications.insert(&application_id)?;
+                } else {
+                    self.minter_applications.remove(&application_id)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_application_call(&mut self, call: ApplicationCall) -> Result<CallResponse, Self::Error> {
+        match call {
+            ApplicationCall::Balance { owner } => {
+                let balance = self.balances.get(&owner).await?.unwrap_or(0);
+                Ok(CallResponse::Balance(balance))
+            }
+            ApplicationCall::Transfer { owner, to, amount } => {
+                self.check_account_permission(&owner)?;
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&owner).await?;
+                self.ensure_not_frozen(&to).await?;
+                self.transfer(owner, to, amount, None).await?;
+                Ok(CallResponse::Ok)
+            }
+            ApplicationCall::TransferFrom { from, to, amount } => {
+                let spender = self.calling_application()?;
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&spender).await?;
+                self.ensure_not_frozen(&from).await?;
+                self.ensure_not_frozen(&to).await?;
+                self.spend_allowance(from, spender, amount).await?;
+                self.transfer(from, to, amount, None).await?;
+                Ok(CallResponse::Ok)
+            }
+            ApplicationCall::Mint { to, amount } => {
+                let caller_id = self.runtime().authenticated_caller_id().ok_or(Error::Unauthorized)?;
+                if !self.minter_applications.contains(&caller_id).await? {
+                    return Err(Error::Unauthorized);
+                }
+                self.mint(to, amount).await?;
+                Ok(CallResponse::Ok)
+            }
+            // The borrower initiates the loan rather than being called back:
+            // Linera rejects reentrant calls, so a borrower called from inside
+            // the token could not move the loan through the token
+            ApplicationCall::FlashMint { amount } => {
+                let borrower = self.runtime().authenticated_caller_id().ok_or(Error::Unauthorized)?;
+                let account = AccountOwner::Application(borrower);
+                self.ensure_not_paused().await?;
+                self.ensure_not_frozen(&account).await?;
+                let total = self.total_supply.get().await?;
+                if amount > checked_sub(MAX_SUPPLY, total)? {
+                    return Err(Error::SupplyCapExceeded);
+                }
+                let owed = self.flash_loans.get(&borrower).await?.unwrap_or(0);
+                let owed = checked_add(owed, checked_add(amount, flash_mint_fee(amount))?)?;
+                self.flash_loans.insert(&borrower, owed)?;
+                self.total_supply.set(checked_add(total, amount)?);
+                self.credit(&account, amount).await?;
+                Ok(CallResponse::Ok)
+            }
+            ApplicationCall::RepayFlashMint => {
+                let borrower = self.runtime().authenticated_caller_id().ok_or(Error::Unauthorized)?;
+                let owed = self.flash_loans.get(&borrower).await?.ok_or(Error::FlashMintNotRepaid)?;
+                let account = AccountOwner::Application(borrower);
+                self.debit(&account, owed).await?;
+                let total = self.total_supply.get().await?;
+                self.total_supply.set(checked_sub(total, owed)?);
+                self.flash_loans.remove(&borrower)?;
+                Ok(CallResponse::Ok)
             }
         }
+    }
+
+    /// Fails, reverting the whole transaction, while a flash mint is unpaid.
+    async fn finalize(&mut self) -> Result<(), Self::Error> {
+        let mut unpaid = false;
+        self.flash_loans
+            .for_each_index_while(|_| {
+                unpaid = true;
+                Ok(false)
+            })
+            .await?;
+        if unpaid {
+            return Err(Error::FlashMintNotRepaid);
+        }
         Ok(())
     }
 }
@@ -95,7 +749,39 @@ impl Service for TokenState {
         let context = ViewStorageContext::from(runtime.root_view_storage_context());
         Ok(Self {
             balances: MapView::load(context.clone().sub("balances"))?,
-            total_supply: RegisterView::load(context.sub("total_supply"))?,
+            holder_count: RegisterView::load(context.clone().sub("holder_count"))?,
+            holders_by_balance: SetView::load(context.clone().sub("holders_by_balance"))?,
+            total_supply: RegisterView::load(context.clone().sub("total_supply"))?,
+            admin: RegisterView::load(context.clone().sub("admin"))?,
+            emission_start: RegisterView::load(context.clone().sub("emission_start"))?,
+            emission_epoch: RegisterView::load(context.clone().sub("emission_epoch"))?,
+            epoch_minted: RegisterView::load(context.clone().sub("epoch_minted"))?,
+            next_grant_id: RegisterView::load(context.clone().sub("next_grant_id"))?,
+            vesting_grants: MapView::load(context.clone().sub("vesting_grants"))?,
+            beneficiary_grants: MapView::load(context.clone().sub("beneficiary_grants"))?,
+            vesting_escrow: RegisterView::load(context.clone().sub("vesting_escrow"))?,
+            reward_distributor: RegisterView::load(context.clone().sub("reward_distributor"))?,
+            stakes: MapView::load(context.clone().sub("stakes"))?,
+            total_staked: RegisterView::load(context.clone().sub("total_staked"))?,
+            total_unbonding: RegisterView::load(context.clone().sub("total_unbonding"))?,
+            reward_per_token: RegisterView::load(context.clone().sub("reward_per_token"))?,
+            reward_pool: RegisterView::load(context.clone().sub("reward_pool"))?,
+            pauser: RegisterView::load(context.clone().sub("pauser"))?,
+            paused: RegisterView::load(context.clone().sub("paused"))?,
+            frozen: SetView::load(context.clone().sub("frozen"))?,
+            allowances: MapView::load(context.clone().sub("allowances"))?,
+            permit_nonces: MapView::load(context.clone().sub("permit_nonces"))?,
+            transfer_history: MapView::load(context.clone().sub("transfer_history"))?,
+            transfer_count: MapView::load(context.clone().sub("transfer_count"))?,
+            next_airdrop_id: RegisterView::load(context.clone().sub("next_airdrop_id"))?,
+            airdrops: MapView::load(context.clone().sub("airdrops"))?,
+            airdrop_claimed: MapView::load(context.clone().sub("airdrop_claimed"))?,
+            airdrop_escrow: RegisterView::load(context.clone().sub("airdrop_escrow"))?,
+            current_snapshot_id: RegisterView::load(context.clone().sub("current_snapshot_id"))?,
+            balance_checkpoints: MapView::load(context.clone().sub("balance_checkpoints"))?,
+            supply_snapshots: MapView::load(context.clone().sub("supply_snapshots"))?,
+            minter_applications: SetView::load(context.clone().sub("minter_applications"))?,
+            flash_loans: MapView::load(context.sub("flash_loans"))?,
         })
     }
 
@@ -109,6 +795,534 @@ impl Service for TokenState {
                 let supply = self.total_supply.get().await?;
                 Ok(serde_json::to_string(&supply)?)
             }
+            Query::GetRemainingMintable => {
+                let supply = self.total_supply.get().await?;
+                Ok(serde_json::to_string(&MAX_SUPPLY.saturating_sub(supply))?)
+            }
+            Query::GetEpochBudget => {
+                let now = self.runtime().system_time();
+                let epoch = self.epoch_at(now).await?;
+                let budget = epoch_budget(epoch);
+                let minted = self.minted_in_epoch(epoch).await?;
+                let supply_left = MAX_SUPPLY.saturating_sub(self.total_supply.get().await?);
+                let info = EpochBudget {
+                    epoch,
+                    budget,
+                    minted,
+                    remaining: budget.saturating_sub(minted).min(supply_left),
+                };
+                Ok(serde_json::to_string(&info)?)
+            }
+            Query::GetVestingGrant { grant_id } => {
+                let grant = self.vesting_grants.get(&grant_id).await?.ok_or(Error::GrantNotFound)?;
+                let now = self.runtime().system_time();
+                let vested = grant.vested_at(now);
+                let status = VestingStatus {
+                    vested,
+                    unvested: grant.total.saturating_sub(vested),
+                    releasable: vested.saturating_sub(grant.released),
+                    grant,
+                };
+                Ok(serde_json::to_string(&status)?)
+            }
+            Query::GetBeneficiaryGrants { beneficiary } => {
+                let grants = self.beneficiary_grants.get(&beneficiary).await?.unwrap_or_default();
+                Ok(serde_json::to_string(&grants)?)
+            }
+            Query::GetStake { owner } => {
+                let reward_per_token = self.reward_per_token.get().await?;
+                let mut stake = self.stakes.get(&owner).await?.unwrap_or_default();
+                stake.settle(reward_per_token)?;
+                Ok(serde_json::to_string(&stake)?)
+            }
+            Query::GetStakingInfo => {
+                let info = StakingInfo {
+                    total_staked: self.total_staked.get().await?,
+                    total_unbonding: self.total_unbonding.get().await?,
+                    reward_pool: self.reward_pool.get().await?,
+                    reward_per_token: self.reward_per_token.get().await?,
+                    distributor: self.reward_distributor.get().await?,
+                };
+                Ok(serde_json::to_string(&info)?)
+            }
+            Query::GetPauseStatus { owner } => {
+                let status = PauseStatus {
+                    paused: self.paused.get().await?,
+                    frozen: self.frozen.contains(&owner).await?,
+                };
+                Ok(serde_json::to_string(&status)?)
+            }
+            Query::GetAllowance { owner, spender } => {
+                let allowance = self.allowances.get(&(owner, spender)).await?.unwrap_or(0);
+                Ok(serde_json::to_string(&allowance)?)
+            }
+            Query::GetPermitNonce { owner } => {
+                let nonce = self.permit_nonces.get(&owner).await?.unwrap_or(0);
+                Ok(serde_json::to_string(&nonce)?)
+            }
+            Query::GetTransferHistory { owner, offset, limit } => {
+                let count = self.transfer_count.get(&owner).await?.unwrap_or(0);
+                let available = count.min(MAX_HISTORY_LEN);
+                let end = available.min(u64::from(offset) + u64::from(limit).min(MAX_PAGE_SIZE as u64));
+                let mut page = Vec::new();
+                // Walk sequence numbers backwards from the newest record
+                for position in u64::from(offset)..end {
+                    let sequence = count - 1 - position;
+                    let slot = (owner, sequence % MAX_HISTORY_LEN);
+                    if let Some(record) = self.transfer_history.get(&slot).await? {
+                        page.push(record);
+                    }
+                }
+                Ok(serde_json::to_string(&page)?)
+            }
+            Query::GetAirdrop { airdrop_id } => {
+                let airdrop = self.airdrops.get(&airdrop_id).await?.ok_or(Error::AirdropNotFound)?;
+                Ok(serde_json::to_string(&airdrop)?)
+            }
+            Query::IsAirdropClaimed { airdrop_id, index } => {
+                let (word_key, bit) = claim_slot(airdrop_id, index);
+                let word = self.airdrop_claimed.get(&word_key).await?.unwrap_or(0);
+                Ok(serde_json::to_string(&(word & bit != 0))?)
+            }
+            Query::GetHolderCount => {
+                let count = self.holder_count.get().await?;
+                Ok(serde_json::to_string(&count)?)
+            }
+            Query::GetHolders { offset, limit } => {
+                let (offset, limit) = (offset as usize, (limit as usize).min(MAX_PAGE_SIZE));
+                let mut holders = Vec::new();
+                let mut skipped = 0;
+                self.balances
+                    .for_each_index_value_while(|owner, balance| {
+                        if skipped < offset {
+                            skipped += 1;
+                        } else {
+                            holders.push(Holder { owner, balance });
+                        }
+                        Ok(holders.len() < limit)
+                    })
+                    .await?;
+                Ok(serde_json::to_string(&holders)?)
+            }
+            Query::GetTopHolders { limit } => {
+                let limit = (limit as usize).min(MAX_TOP_HOLDERS);
+                let mut top: Vec<Holder> = Vec::with_capacity(limit);
+                self.holders_by_balance
+                    .for_each_index_while(|(key, owner)| {
+                        if top.len() == limit {
+                            return Ok(false);
+                        }
+                        top.push(Holder { owner, balance: balance_from_rank_key(key) });
+                        Ok(true)
+                    })
+                    .await?;
+                Ok(serde_json::to_string(&top)?)
+            }
+            Query::GetCurrentSnapshotId => {
+                let id = self.current_snapshot_id.get().await?;
+                Ok(serde_json::to_string(&id)?)
+            }
+            Query::GetBalanceAt { owner, snapshot_id } => {
+                self.ensure_snapshot_exists(snapshot_id).await?;
+                // Walks the snapshots taken since, oldest first, stopping at
+                // the first checkpoint
+                let current_snapshot_id = self.current_snapshot_id.get().await?;
+                let mut checkpoints = Vec::new();
+                for id in snapshot_id..=current_snapshot_id {
+                    let checkpoint = self.balance_checkpoints.get(&(owner, id)).await?;
+                    let found = checkpoint.is_some();
+                    checkpoints.push(checkpoint);
+                    if found {
+                        break;
+                    }
+                }
+                let current = self.balances.get(&owner).await?.unwrap_or(0);
+                let balance = balance_at_snapshot(checkpoints, current);
+                Ok(serde_json::to_string(&balance)?)
+            }
+            Query::GetTotalSupplyAt { snapshot_id } => {
+                self.ensure_snapshot_exists(snapshot_id).await?;
+                let supply = self.supply_snapshots.get(&snapshot_id).await?.unwrap_or(0);
+                Ok(serde_json::to_string(&supply)?)
+            }
+            Query::CheckSupplyInvariant => {
+                // `None` once the sum overflows
+                let mut sum = Some(0u128);
+                self.balances
+                    .for_each_index_value(|_, balance| {
+                        sum = sum.and_then(|sum| sum.checked_add(balance));
+                        Ok(())
+                    })
+                    .await?;
+                let sum_of_balances = sum.ok_or(Error::ArithmeticOverflow)?;
+                let vesting_escrow = self.vesting_escrow.get().await?;
+                let total_staked = self.total_staked.get().await?;
+                let total_unbonding = self.total_unbonding.get().await?;
+                let reward_pool = self.reward_pool.get().await?;
+                let airdrop_escrow = self.airdrop_escrow.get().await?;
+                let accounted = [vesting_escrow, total_staked, total_unbonding, reward_pool, airdrop_escrow]
+                    .into_iter()
+                    .try_fold(sum_of_balances, checked_add)?;
+                let total_supply = self.total_supply.get().await?;
+                let invariant = SupplyInvariant {
+                    sum_of_balances,
+                    vesting_escrow,
+                    total_staked,
+                    total_unbonding,
+                    reward_pool,
+                    airdrop_escrow,
+                    total_supply,
+                    holds: accounted == total_supply,
+                };
+                Ok(serde_json::to_string(&invariant)?)
+            }
+            Query::GetFlashMintFee { amount } => {
+                Ok(serde_json::to_string(&flash_mint_fee(amount))?)
+            }
+            Query::IsMinterApplication { application_id } => {
+                let authorized = self.minter_applications.contains(&application_id).await?;
+                Ok(serde_json::to_string(&authorized)?)
+            }
+        }
+    }
+}
+
+impl TokenState {
+    /// Mints within the supply cap and the current epoch budget.
+    async fn mint(&mut self, to: AccountOwner, amount: u128) -> Result<(), Error> {
+        self.ensure_not_paused().await?;
+        self.ensure_not_frozen(&to).await?;
+        let total = self.total_supply.get().await?;
+        if amount > checked_sub(MAX_SUPPLY, total)? {
+            return Err(Error::SupplyCapExceeded);
+        }
+        let now = self.runtime().system_time();
+        let epoch = self.epoch_at(now).await?;
+        let minted = self.minted_in_epoch(epoch).await?;
+        if amount > checked_sub(epoch_budget(epoch), minted)? {
+            return Err(Error::EpochBudgetExceeded);
+        }
+        self.emission_epoch.set(epoch);
+        self.epoch_minted.set(checked_add(minted, amount)?);
+        self.total_supply.set(checked_add(total, amount)?);
+        self.credit(&to, amount).await
+    }
+
+    async fn credit(&mut self, owner: &AccountOwner, amount: u128) -> Result<(), Error> {
+        let balance = self.balances.get(owner).await?.unwrap_or(0);
+        self.set_balance(owner, balance, checked_add(balance, amount)?).await
+    }
+
+    async fn debit(&mut self, owner: &AccountOwner, amount: u128) -> Result<(), Error> {
+        let balance = self.balances.get(owner).await?.unwrap_or(0);
+        let remaining = balance.checked_sub(amount).ok_or(Error::InsufficientBalance)?;
+        self.set_balance(owner, balance, remaining).await
+    }
+
+    /// Writes a balance change, dropping empty entries and keeping
+    /// `holder_count` and `holders_by_balance` in step.
+    async fn set_balance(&mut self, owner: &AccountOwner, old: u128, new: u128) -> Result<(), Error> {
+        if old == new {
+            return Ok(());
+        }
+        self.checkpoint_balance(owner, old).await?;
+        let holders = self.holder_count.get().await?;
+        if old != 0 {
+            self.holders_by_balance.remove(&(rank_key(old), *owner))?;
+        }
+        if new != 0 {
+            self.holders_by_balance.insert(&(rank_key(new), *owner))?;
+        }
+        if new == 0 {
+            self.balances.remove(owner)?;
+            let holders = holders.checked_sub(1).ok_or(Error::ArithmeticOverflow)?;
+            self.holder_count.set(holders);
+        } else {
+            self.balances.insert(owner, new)?;
+            if old == 0 {
+                let holders = holders.checked_add(1).ok_or(Error::ArithmeticOverflow)?;
+                self.holder_count.set(holders);
+            }
+        }
+        Ok(())
+    }
+
+    /// Records `balance` as the owner's balance at the current snapshot, unless
+    /// it was already recorded since that snapshot was taken.
+    async fn checkpoint_balance(&mut self, owner: &AccountOwner, balance: u128) -> Result<(), Error> {
+        let snapshot_id = self.current_snapshot_id.get().await?;
+        if snapshot_id == 0 {
+            return Ok(());
         }
+        let key = (*owner, snapshot_id);
+        if !self.balance_checkpoints.contains_key(&key).await? {
+            self.balance_checkpoints.insert(&key, balance)?;
+        }
+        Ok(())
+    }
+
+    async fn ensure_snapshot_exists(&self, snapshot_id: u64) -> Result<(), Error> {
+        if snapshot_id == 0 || snapshot_id > self.current_snapshot_id.get().await? {
+            return Err(Error::SnapshotNotFound);
+        }
+        Ok(())
+    }
+
+    /// Moves `amount` between accounts and records it in both histories.
+    async fn transfer(
+        &mut self,
+        from: AccountOwner,
+        to: AccountOwner,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<(), Error> {
+        if amount == 0 {
+            return Err(Error::ZeroAmount);
+        }
+        if memo.as_ref().is_some_and(|memo| memo.len() > MAX_MEMO_LEN) {
+            return Err(Error::MemoTooLong);
+        }
+        self.debit(&from, amount).await?;
+        self.credit(&to, amount).await?;
+
+        let timestamp = self.runtime().system_time();
+        let outgoing = TransferRecord {
+            counterparty: to,
+            amount,
+            direction: TransferDirection::Outgoing,
+            timestamp,
+            memo: memo.clone(),
+        };
+        self.record_transfer(from, outgoing).await?;
+        let incoming = TransferRecord {
+            counterparty: from,
+            amount,
+            direction: TransferDirection::Incoming,
+            timestamp,
+            memo,
+        };
+        self.record_transfer(to, incoming).await?;
+        Ok(())
+    }
+
+    async fn record_transfer(
+        &mut self,
+        owner: AccountOwner,
+        record: TransferRecord,
+    ) -> Result<(), Error> {
+        // Overwrites the oldest slot once the ring is full
+        let count = self.transfer_count.get(&owner).await?.unwrap_or(0);
+        self.transfer_history.insert(&(owner, count % MAX_HISTORY_LEN), record)?;
+        self.transfer_count.insert(&owner, count + 1)?;
+        Ok(())
+    }
+
+    async fn spend_allowance(
+        &mut self,
+        owner: AccountOwner,
+        spender: AccountOwner,
+        amount: u128,
+    ) -> Result<(), Error> {
+        let allowance = self.allowances.get(&(owner, spender)).await?.unwrap_or(0);
+        let remaining = allowance.checked_sub(amount).ok_or(Error::InsufficientAllowance)?;
+        self.allowances.insert(&(owner, spender), remaining)?;
+        Ok(())
+    }
+
+    /// Account of the application making the current call.
+    fn calling_application(&mut self) -> Result<AccountOwner, Error> {
+        let caller_id = self.runtime().authenticated_caller_id().ok_or(Error::Unauthorized)?;
+        Ok(AccountOwner::Application(caller_id))
+    }
+
+    /// Checks that `owner` is either the calling application or the signer
+    /// of the block, as forwarded by the caller.
+    fn check_account_permission(&mut self, owner: &AccountOwner) -> Result<(), Error> {
+        let caller_app = self.runtime().authenticated_caller_id().map(AccountOwner::Application);
+        let signer = self.runtime().authenticated_signer();
+        if caller_app.as_ref() == Some(owner) || signer == *owner {
+            return Ok(());
+        }
+        Err(Error::Unauthorized)
+    }
+
+    async fn ensure_pauser(&self, caller: &AccountOwner) -> Result<(), Error> {
+        if Some(*caller) != self.pauser.get().await? {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    async fn ensure_not_paused(&self) -> Result<(), Error> {
+        if self.paused.get().await? {
+            return Err(Error::Paused);
+        }
+        Ok(())
+    }
+
+    async fn ensure_not_frozen(&self, owner: &AccountOwner) -> Result<(), Error> {
+        if self.frozen.contains(owner).await? {
+            return Err(Error::AccountFrozen);
+        }
+        Ok(())
+    }
+
+    /// Emission epoch that `now` falls into, counted from contract creation.
+    async fn epoch_at(&self, now: Timestamp) -> Result<u64, Error> {
+        let start = self.emission_start.get().await?;
+        Ok(now.micros().saturating_sub(start.micros()) / EPOCH_DURATION_MICROS)
+    }
+
+    /// Amount already minted during `epoch`; the counter resets on the first
+    /// mint of a new epoch.
+    async fn minted_in_epoch(&self, epoch: u64) -> Result<u128, Error> {
+        if epoch == self.emission_epoch.get().await? {
+            Ok(self.epoch_minted.get().await?)
+        } else {
+            Ok(0)
+        }
+    }
+}
+
+/// Bitmap word key and bit mask recording whether `index` was claimed.
+fn claim_slot(airdrop_id: u64, index: u64) -> ((u64, u64), u128) {
+    let word_key = (airdrop_id, index / CLAIM_BITMAP_WORD_BITS);
+    (word_key, 1u128 << (index % CLAIM_BITMAP_WORD_BITS))
+}
+
+fn checked_add(a: u128, b: u128) -> Result<u128, Error> {
+    a.checked_add(b).ok_or(Error::ArithmeticOverflow)
+}
+
+fn checked_sub(a: u128, b: u128) -> Result<u128, Error> {
+    a.checked_sub(b).ok_or(Error::ArithmeticOverflow)
+}
+
+/// Balance at a snapshot, given the checkpoints of the snapshots taken since
+/// it, oldest first. The first checkpoint holds the balance at that time;
+/// without one the balance has not changed since.
+fn balance_at_snapshot(checkpoints: impl IntoIterator<Item = Option<u128>>, current: u128) -> u128 {
+    checkpoints.into_iter().flatten().next().unwrap_or(current)
+}
+
+/// Key ordering `holders_by_balance` largest balance first: the inverted
+/// balance in big-endian bytes, which serializes and sorts as raw bytes.
+fn rank_key(balance: u128) -> [u8; 16] {
+    (u128::MAX - balance).to_be_bytes()
+}
+
+fn balance_from_rank_key(key: [u8; 16]) -> u128 {
+    u128::MAX - u128::from_be_bytes(key)
+}
+
+fn flash_mint_fee(amount: u128) -> u128 {
+    amount / BPS_DENOMINATOR * FLASH_MINT_FEE_BPS
+        + amount % BPS_DENOMINATOR * FLASH_MINT_FEE_BPS / BPS_DENOMINATOR
+}
+
+/// Maximum amount that may be minted during `epoch`.
+fn epoch_budget(epoch: u64) -> u128 {
+    let halvings = epoch / EPOCHS_PER_HALVING;
+    if halvings >= u128::BITS as u64 {
+        return 0;
+    }
+    INITIAL_EPOCH_BUDGET >> halvings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_keys_sort_largest_balance_first() {
+        let mut keys = vec![rank_key(5), rank_key(u128::MAX), rank_key(1), rank_key(300)];
+        keys.sort();
+        let balances: Vec<u128> = keys.into_iter().map(balance_from_rank_key).collect();
+        assert_eq!(balances, vec![u128::MAX, 300, 5, 1]);
+    }
+
+    #[test]
+    fn balance_at_snapshot_uses_first_later_checkpoint() {
+        assert_eq!(balance_at_snapshot([None, Some(5), Some(9)], 20), 5);
+        assert_eq!(balance_at_snapshot([Some(0), Some(9)], 20), 0);
+    }
+
+    #[test]
+    fn balance_at_snapshot_defaults_to_current_balance() {
+        assert_eq!(balance_at_snapshot([None, None], 20), 20);
+        assert_eq!(balance_at_snapshot([], 20), 20);
+    }
+
+    #[test]
+    fn flash_mint_fee_rounds_down() {
+        assert_eq!(flash_mint_fee(0), 0);
+        assert_eq!(flash_mint_fee(1_111), 0);
+        assert_eq!(flash_mint_fee(1_112), 1);
+        assert_eq!(flash_mint_fee(10_000), 9);
+        assert_eq!(flash_mint_fee(25_000), 22);
+    }
+
+    #[test]
+    fn flash_mint_fee_does_not_overflow() {
+        assert_eq!(flash_mint_fee(u128::MAX), 306_254_130_228_844_617_117_037_146_688_591_390);
+    }
+
+    #[test]
+    fn epoch_budget_halves_every_period() {
+        assert_eq!(epoch_budget(0), INITIAL_EPOCH_BUDGET);
+        assert_eq!(epoch_budget(EPOCHS_PER_HALVING - 1), INITIAL_EPOCH_BUDGET);
+        assert_eq!(epoch_budget(EPOCHS_PER_HALVING), INITIAL_EPOCH_BUDGET / 2);
+        assert_eq!(epoch_budget(3 * EPOCHS_PER_HALVING), INITIAL_EPOCH_BUDGET / 8);
+    }
+
+    #[test]
+    fn epoch_budget_reaches_zero() {
+        assert_eq!(epoch_budget(128 * EPOCHS_PER_HALVING), 0);
+        assert_eq!(epoch_budget(u64::MAX), 0);
+    }
+
+    fn grant(total: u128, revoked: bool) -> VestingGrant {
+        VestingGrant {
+            beneficiary: AccountOwner::from(CryptoHash::test_hash("beneficiary")),
+            total,
+            released: 0,
+            start: Timestamp::from(1_000),
+            cliff_micros: 100,
+            duration_micros: 1_000,
+            revocable: true,
+            revoked,
+        }
+    }
+
+    #[test]
+    fn nothing_vests_before_cliff() {
+        let grant = grant(10_000, false);
+        assert_eq!(grant.vested_at(Timestamp::from(0)), 0);
+        assert_eq!(grant.vested_at(Timestamp::from(1_000)), 0);
+        assert_eq!(grant.vested_at(Timestamp::from(1_099)), 0);
+    }
+
+    #[test]
+    fn vests_linearly_from_start_after_cliff() {
+        let grant = grant(10_000, false);
+        assert_eq!(grant.vested_at(Timestamp::from(1_100)), 1_000);
+        assert_eq!(grant.vested_at(Timestamp::from(1_500)), 5_000);
+        assert_eq!(grant.vested_at(Timestamp::from(2_000)), 10_000);
+        assert_eq!(grant.vested_at(Timestamp::from(5_000)), 10_000);
+    }
+
+    #[test]
+    fn linear_vesting_rounds_down_without_overflow() {
+        let large = grant(u128::MAX, false);
+        assert_eq!(large.vested_at(Timestamp::from(1_500)), u128::MAX / 2);
+        let small = grant(7, false);
+        assert_eq!(small.vested_at(Timestamp::from(1_500)), 3);
+    }
+
+    #[test]
+    fn revoked_grant_keeps_total_at_revocation() {
+        let grant = grant(4_000, true);
+        assert_eq!(grant.vested_at(Timestamp::from(0)), 4_000);
+        assert_eq!(grant.vested_at(Timestamp::from(1_500)), 4_000);
     }
 }
\ No newline at end of file